@@ -1,5 +1,10 @@
 use std::sync::Arc;
 
+use alkanes_proxy_support::admin::ProxyAdmin;
+use alkanes_proxy_support::pause::Pausable;
+use alkanes_proxy_support::policy::TargetPolicy;
+use alkanes_proxy_support::witness::read_witness_cellpack;
+use alkanes_proxy_support::ProxyError;
 use alkanes_runtime::runtime::AlkaneResponder;
 use alkanes_runtime::storage::StoragePointer;
 use alkanes_runtime::{auth::AuthenticatedResponder, declare_alkane, message::MessageDispatch};
@@ -12,46 +17,354 @@ use alkanes_support::cellpack::Cellpack;
 use alkanes_support::id::AlkaneId;
 use alkanes_support::parcel::AlkaneTransferParcel;
 use alkanes_support::{context::Context, parcel::AlkaneTransfer, response::CallResponse};
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use metashrew_support::compat::{to_arraybuffer_layout, to_passback_ptr};
+use metashrew_support::index_pointer::KeyValuePointer;
+
+pub mod mint;
+use mint::{MintPolicy, MintTarget, DEFAULT_MINT_CONFIG};
 
 #[derive(Default)]
 pub struct DieselProxy(());
 
+impl AuthenticatedResponder for DieselProxy {}
+
+impl ProxyAdmin for DieselProxy {}
+
+impl TargetPolicy for DieselProxy {}
+
+impl Pausable for DieselProxy {}
+
 #[derive(MessageDispatch)]
 enum DieselProxyMessage {
     #[opcode(69690420)]
     Initialize {},
+    #[opcode(69690421)]
+    GetMintConfig {},
+    #[opcode(69690422)]
+    BestEffort {},
+    #[opcode(69690423)]
+    MintIntoCall {},
+    #[opcode(69690424)]
+    MintOnly {},
+    #[opcode(69690425)]
+    CallOnly {},
+    #[opcode(69690426)]
+    MintAndCall {},
+    #[opcode(69690427)]
+    MintAndCallFromWitness {},
+    #[opcode(69690428)]
+    SetTargetPolicy { mode: u128 },
+    #[opcode(69690429)]
+    SetTargetListed { block: u128, tx: u128, listed: u128 },
+    #[opcode(69690430)]
+    IsTargetAllowed { block: u128, tx: u128 },
+    #[opcode(69690431)]
+    Pause {},
+    #[opcode(69690432)]
+    Unpause {},
+    #[opcode(69690433)]
+    IsPaused {},
+    #[opcode(69690434)]
+    MintAuthToken { amount: u128 },
+    #[opcode(69690435)]
+    TransferOwnership { block: u128, tx: u128 },
+    #[opcode(69690436)]
+    AcceptOwnership {},
+    #[opcode(69690437)]
+    RenounceOwnership {},
+    #[opcode(69690438)]
+    GetAdmin {},
+}
+
+/// How a call through the proxy treats the mint.
+#[derive(Clone, Copy, Default)]
+struct MintOptions {
+    /// Failing mints never revert the call, and the response data is
+    /// prefixed with one flag per mint target.
+    best_effort: bool,
+    /// The minted alkanes are sent into the target call along with the
+    /// incoming alkanes, instead of being paid out after it.
+    feed_call: bool,
+}
+
+/// Where the proxied call is read from.
+#[derive(Clone, Copy)]
+enum CallSource {
+    /// `[block, tx, ...inputs]`, starting at this index of the inputs.
+    Inputs(usize),
+    /// The witness envelope, in the witness proxy payload format.
+    Witness,
 }
 
 impl DieselProxy {
+    /// Stores the mint policy and the free-mint alkanes the fallback mints
+    /// from, read from the inputs `[policy, block, tx, opcode, ...]` with one
+    /// `[block, tx, opcode]` triple per target, e.g. `0, 2, 0, 77` to mint
+    /// DIESEL only and revert when that mint fails. Without inputs the proxy
+    /// is configured with `DEFAULT_MINT_CONFIG`. The auth token guarding the
+    /// admin opcodes is minted to the caller.
     fn initialize(&self) -> Result<CallResponse> {
+        self.observe_initialization()?;
         let context = self.context()?;
-        let response: CallResponse = CallResponse::forward(&context.incoming_alkanes.clone());
+        let config = match &context.inputs[1..] {
+            [] => &DEFAULT_MINT_CONFIG[..],
+            config => config,
+        };
+        let (policy, targets) = config
+            .split_first()
+            .ok_or_else(|| ProxyError::InvalidInput("missing mint policy".to_string()))?;
+        let policy = MintPolicy::try_from(*policy)?;
+        let targets = MintTarget::parse_list(targets)?;
+        self.mint_policy_pointer().set_value::<u128>(policy as u128);
+        let mut targets_pointer = self.mint_targets_pointer();
+        targets_pointer.set_value::<u128>(targets.len() as u128);
+        for (i, target) in targets.into_iter().enumerate() {
+            targets_pointer
+                .select(&(i as u128).to_le_bytes().to_vec())
+                .set(Arc::new(target.into()));
+        }
+        let mut response: CallResponse = CallResponse::forward(&context.incoming_alkanes.clone());
+        response.alkanes.pay(self.deploy_auth_token(1)?);
         Ok(response)
     }
-}
 
-impl AlkaneResponder for DieselProxy {
-    fn fallback(&self) -> Result<CallResponse> {
+    fn set_target_policy(&self, mode: u128) -> Result<CallResponse> {
+        TargetPolicy::set_target_policy(self, mode)
+    }
+
+    fn set_target_listed(&self, block: u128, tx: u128, listed: u128) -> Result<CallResponse> {
+        TargetPolicy::set_target_listed(self, block, tx, listed)
+    }
+
+    fn is_target_allowed(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        TargetPolicy::is_target_allowed(self, block, tx)
+    }
+
+    fn pause(&self) -> Result<CallResponse> {
+        Pausable::pause(self)
+    }
+
+    fn unpause(&self) -> Result<CallResponse> {
+        Pausable::unpause(self)
+    }
+
+    fn is_paused(&self) -> Result<CallResponse> {
+        Pausable::is_paused(self)
+    }
+
+    fn mint_auth_token(&self, amount: u128) -> Result<CallResponse> {
+        ProxyAdmin::mint_auth_token(self, amount)
+    }
+
+    fn transfer_ownership(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        ProxyAdmin::transfer_ownership(self, block, tx)
+    }
+
+    fn accept_ownership(&self) -> Result<CallResponse> {
+        ProxyAdmin::accept_ownership(self)
+    }
+
+    fn renounce_ownership(&self) -> Result<CallResponse> {
+        ProxyAdmin::renounce_ownership(self)
+    }
+
+    fn get_admin(&self) -> Result<CallResponse> {
+        ProxyAdmin::get_admin(self)
+    }
+
+    /// Returns the configuration in the layout `Initialize` takes, `[policy,
+    /// block, tx, opcode, ...]`, each value as a little endian u128.
+    fn get_mint_config(&self) -> Result<CallResponse> {
         let context = self.context()?;
-        let inputs: Vec<u128> = context.inputs.clone();
-        let cellpack = Cellpack {
-            target: AlkaneId::new(inputs[0], inputs[1]),
-            inputs: inputs[2..].to_vec(),
-        };
-        let diesel_mint = self.call(
-            &Cellpack {
-                target: AlkaneId::new(2, 0),
-                inputs: vec![77],
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response
+            .data
+            .extend((self.mint_policy()? as u128).to_le_bytes());
+        for target in self.mint_targets()?.iter() {
+            for value in target.to_values() {
+                response.data.extend(value.to_le_bytes());
+            }
+        }
+        Ok(response)
+    }
+
+    /// Same as the fallback, with the inputs `[block, tx, ...inputs]` following
+    /// the opcode, except that failing mints never revert the call, whatever
+    /// the configured policy. The response data starts with one byte per mint
+    /// target, `1` if that mint happened and `0` otherwise, followed by the
+    /// data returned by the target.
+    fn best_effort(&self) -> Result<CallResponse> {
+        self.execute(
+            CallSource::Inputs(1),
+            MintOptions {
+                best_effort: true,
+                ..Default::default()
             },
-            &AlkaneTransferParcel(vec![]),
-            self.fuel(),
-        )?;
-        let mut arb_call = self.call(&cellpack, &context.incoming_alkanes, self.fuel())?;
-        arb_call.alkanes.pay(diesel_mint.alkanes.0[0]);
+        )
+    }
+
+    /// Same as the fallback, with the inputs `[block, tx, ...inputs]` following
+    /// the opcode, except that the minted alkanes are sent into the target
+    /// call along with the incoming alkanes, so the target can spend them
+    /// right away.
+    fn mint_into_call(&self) -> Result<CallResponse> {
+        self.execute(
+            CallSource::Inputs(1),
+            MintOptions {
+                feed_call: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Mints from every configured target without calling anything. The
+    /// incoming alkanes are returned along with the minted ones.
+    fn mint_only(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        if self.paused() {
+            return Ok(CallResponse::forward(&context.incoming_alkanes));
+        }
+        let (minted, _) = self.mint_all(self.mint_policy()? == MintPolicy::SkipFailed)?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.extend(minted.0);
+        Ok(response)
+    }
+
+    /// Calls the target `[block, tx, ...inputs]` following the opcode without
+    /// minting anything.
+    fn call_only(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        if self.paused() {
+            return Ok(CallResponse::forward(&context.incoming_alkanes));
+        }
+        let cellpack = target_cellpack(&context.inputs[1..])?;
+        self.check_target(&cellpack.target)?;
+        self.call(&cellpack, &context.incoming_alkanes, self.fuel())
+            .map_err(|e| ProxyError::TargetCallFailed(e.to_string()).into())
+    }
+
+    /// Same as the fallback, with the inputs `[block, tx, ...inputs]` following
+    /// the opcode. Prefer it over the fallback so that a target block can
+    /// never be mistaken for one of the proxy's own opcodes.
+    fn mint_and_call(&self) -> Result<CallResponse> {
+        self.execute(CallSource::Inputs(1), MintOptions::default())
+    }
+
+    /// Mints like the fallback, but reads the call from the witness envelope
+    /// the way the witness proxy does, so calls too large for OP_RETURN can
+    /// still collect the free mint. Signed payloads are rejected.
+    fn mint_and_call_from_witness(&self) -> Result<CallResponse> {
+        self.execute(CallSource::Witness, MintOptions::default())
+    }
+
+    /// Reads the call from `source` and runs it with the mint. A paused proxy
+    /// refunds the incoming alkanes before reading anything, so a call it
+    /// could not parse is refunded rather than reverted.
+    fn execute(&self, source: CallSource, options: MintOptions) -> Result<CallResponse> {
+        let context = self.context()?;
+        if self.paused() {
+            return Ok(CallResponse::forward(&context.incoming_alkanes));
+        }
+        let cellpack = match source {
+            CallSource::Inputs(start) => target_cellpack(&context.inputs[start..])?,
+            CallSource::Witness => read_witness_cellpack(&self.transaction_object()?)?,
+        };
+        self.check_target(&cellpack.target)?;
+        let skip_failed = options.best_effort || self.mint_policy()? == MintPolicy::SkipFailed;
+        let (minted, flags) = self.mint_all(skip_failed)?;
+        let mut incoming = context.incoming_alkanes.clone();
+        if options.feed_call {
+            incoming.0.extend(minted.0.iter().cloned());
+        }
+        let mut arb_call = self
+            .call(&cellpack, &incoming, self.fuel())
+            .map_err(|e| ProxyError::TargetCallFailed(e.to_string()))?;
+        if options.best_effort {
+            arb_call.data = [flags, arb_call.data].concat();
+        }
+        if !options.feed_call {
+            arb_call.alkanes.0.extend(minted.0);
+        }
         Ok(arb_call)
     }
+
+    /// Mints from every configured target in order, returning everything
+    /// minted and one flag per target telling whether its mint happened.
+    fn mint_all(&self, skip_failed: bool) -> Result<(AlkaneTransferParcel, Vec<u8>)> {
+        let mut minted = AlkaneTransferParcel::default();
+        let mut flags = Vec::new();
+        for target in self.mint_targets()?.iter() {
+            match self.mint(&target.cellpack()) {
+                Ok(parcel) => {
+                    minted.0.extend(parcel.0);
+                    flags.push(1);
+                }
+                Err(_) if skip_failed => flags.push(0),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((minted, flags))
+    }
+
+    fn mint(&self, mint: &Cellpack) -> Result<AlkaneTransferParcel> {
+        let response = self
+            .call(mint, &AlkaneTransferParcel(vec![]), self.fuel())
+            .map_err(|e| ProxyError::MintFailed(e.to_string()))?;
+        if response.alkanes.0.is_empty() {
+            return Err(ProxyError::MintReturnedNothing.into());
+        }
+        Ok(response.alkanes)
+    }
+
+    fn mint_policy_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/mint/policy")
+    }
+
+    fn mint_targets_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/mint/targets")
+    }
+
+    fn mint_policy(&self) -> Result<MintPolicy> {
+        self.mint_policy_pointer().get_value::<u128>().try_into()
+    }
+
+    fn mint_targets(&self) -> Result<Vec<MintTarget>> {
+        let pointer = self.mint_targets_pointer();
+        let count = pointer.get_value::<u128>();
+        if count == 0 {
+            return Err(
+                ProxyError::NotConfigured("mint targets are not configured".to_string()).into(),
+            );
+        }
+        (0..count)
+            .map(|i| {
+                pointer
+                    .select(&i.to_le_bytes().to_vec())
+                    .get()
+                    .as_ref()
+                    .clone()
+                    .try_into()
+            })
+            .collect()
+    }
+}
+
+/// Reads the proxied call from `[block, tx, ...inputs]`.
+fn target_cellpack(inputs: &[u128]) -> Result<Cellpack> {
+    if inputs.len() < 2 {
+        return Err(ProxyError::CellpackTooShort.into());
+    }
+    Ok(Cellpack {
+        target: AlkaneId::new(inputs[0], inputs[1]),
+        inputs: inputs[2..].to_vec(),
+    })
+}
+
+impl AlkaneResponder for DieselProxy {
+    fn fallback(&self) -> Result<CallResponse> {
+        self.execute(CallSource::Inputs(0), MintOptions::default())
+    }
 }
 // Use the new macro format
 declare_alkane! {
@@ -0,0 +1,89 @@
+use alkanes_proxy_support::ProxyError;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+
+/// The configuration of a proxy initialized without one: DIESEL minted with
+/// opcode `77` under the strict policy, as the proxy always did before the
+/// mint became configurable.
+pub const DEFAULT_MINT_CONFIG: [u128; 4] = [0, 2, 0, 77];
+
+/// A free-mint alkane the proxy mints from, and the opcode that mints it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintTarget {
+    pub id: AlkaneId,
+    pub opcode: u128,
+}
+
+impl MintTarget {
+    pub fn cellpack(&self) -> Cellpack {
+        Cellpack {
+            target: self.id,
+            inputs: vec![self.opcode],
+        }
+    }
+
+    /// Reads `[block, tx, opcode]` triples, as passed to `Initialize`.
+    pub fn parse_list(values: &[u128]) -> Result<Vec<MintTarget>> {
+        if values.is_empty() || values.len() % 3 != 0 {
+            return Err(ProxyError::InvalidInput(
+                "mint targets must be [block, tx, opcode] triples".to_string(),
+            )
+            .into());
+        }
+        Ok(values
+            .chunks(3)
+            .map(|target| MintTarget {
+                id: AlkaneId::new(target[0], target[1]),
+                opcode: target[2],
+            })
+            .collect())
+    }
+
+    pub fn to_values(&self) -> [u128; 3] {
+        [self.id.block, self.id.tx, self.opcode]
+    }
+}
+
+impl From<MintTarget> for Vec<u8> {
+    fn from(target: MintTarget) -> Vec<u8> {
+        let mut bytes: Vec<u8> = target.id.into();
+        bytes.extend(target.opcode.to_le_bytes());
+        bytes
+    }
+}
+
+impl TryFrom<Vec<u8>> for MintTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() != 48 {
+            return Err(anyhow!("stored mint target has {} bytes", bytes.len()));
+        }
+        Ok(MintTarget {
+            id: bytes[..32].to_vec().try_into()?,
+            opcode: u128::from_le_bytes(bytes[32..].try_into()?),
+        })
+    }
+}
+
+/// What happens when minting from one of the targets fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintPolicy {
+    /// Any failing mint reverts the whole call.
+    Strict = 0,
+    /// Failing mints are skipped and the call goes ahead.
+    SkipFailed = 1,
+}
+
+impl TryFrom<u128> for MintPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: u128) -> Result<Self> {
+        match policy {
+            0 => Ok(MintPolicy::Strict),
+            1 => Ok(MintPolicy::SkipFailed),
+            _ => Err(ProxyError::InvalidInput(format!("unknown mint policy {}", policy)).into()),
+        }
+    }
+}
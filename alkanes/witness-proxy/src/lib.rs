@@ -1,5 +1,10 @@
 use std::sync::Arc;
 
+use alkanes_proxy_support::admin::ProxyAdmin;
+use alkanes_proxy_support::assertion::{self, Assertion};
+use alkanes_proxy_support::pause::Pausable;
+use alkanes_proxy_support::policy::TargetPolicy;
+use alkanes_proxy_support::ProxyError;
 use alkanes_runtime::runtime::AlkaneResponder;
 use alkanes_runtime::storage::StoragePointer;
 use alkanes_runtime::{auth::AuthenticatedResponder, declare_alkane, message::MessageDispatch};
@@ -9,16 +14,19 @@ use alkanes_runtime::{
     stdio::{stdout, Write},
 };
 use alkanes_std_factory_support::MintableToken;
-use alkanes_support::witness::find_witness_payload;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::parcel::AlkaneTransferParcel;
 use alkanes_support::{context::Context, parcel::AlkaneTransfer, response::CallResponse};
-use anyhow::{anyhow, Result};
-use bitcoin::hashes::Hash;
-use bitcoin::{Transaction, Txid};
+use anyhow::Result;
 use metashrew_support::compat::{to_arraybuffer_layout, to_passback_ptr};
 use metashrew_support::index_pointer::KeyValuePointer;
-use metashrew_support::utils::consensus_decode;
-use protorune_support::utils::decode_varint_list;
-use std::io::Cursor;
+
+pub use alkanes_proxy_support::payload;
+pub use alkanes_proxy_support::signature;
+pub use alkanes_proxy_support::witness::{
+    extract_witness_payload, extract_witness_payload_at, locate_witness_payload,
+};
+use payload::PayloadHeader;
 
 #[derive(Default)]
 pub struct WitnessProxy(());
@@ -27,43 +35,501 @@ impl MintableToken for WitnessProxy {}
 
 impl AuthenticatedResponder for WitnessProxy {}
 
+impl ProxyAdmin for WitnessProxy {}
+
+impl TargetPolicy for WitnessProxy {}
+
+impl Pausable for WitnessProxy {}
+
 #[derive(MessageDispatch)]
 enum WitnessProxyMessage {
     #[opcode(69690)]
-    Initialize {},
+    Initialize {
+        signer_key_0: u128,
+        signer_key_1: u128,
+    },
+    #[opcode(69691)]
+    ExecuteBatch {},
+    #[opcode(69692)]
+    ExecutePipe {},
+    #[opcode(69693)]
+    ExecuteFromInput {},
+    #[opcode(69694)]
+    GetNonce {
+        signer_key_0: u128,
+        signer_key_1: u128,
+    },
+    #[opcode(69695)]
+    DecodePayload { mode: u128 },
+    #[opcode(69696)]
+    SetTargetPolicy { mode: u128 },
+    #[opcode(69697)]
+    SetTargetListed { block: u128, tx: u128, listed: u128 },
+    #[opcode(69698)]
+    IsTargetAllowed { block: u128, tx: u128 },
+    #[opcode(69699)]
+    Pause {},
+    #[opcode(69700)]
+    Unpause {},
+    #[opcode(69701)]
+    IsPaused {},
+    #[opcode(69702)]
+    MintAuthToken { amount: u128 },
+    #[opcode(69703)]
+    TransferOwnership { block: u128, tx: u128 },
+    #[opcode(69704)]
+    AcceptOwnership {},
+    #[opcode(69705)]
+    RenounceOwnership {},
+    #[opcode(69706)]
+    GetAdmin {},
+    #[opcode(69707)]
+    ExecuteTry {},
+    #[opcode(69708)]
+    DecodePayloadFromInput {},
 }
 
 impl WitnessProxy {
-    fn initialize(&self) -> Result<CallResponse> {
+    /// Registers the x-only public key payloads must be signed with, given as
+    /// two little endian halves. Passing zero for both leaves the proxy open
+    /// to unsigned payloads. The auth token guarding the admin opcodes is
+    /// minted to the caller.
+    fn initialize(&self, signer_key_0: u128, signer_key_1: u128) -> Result<CallResponse> {
+        self.observe_initialization()?;
         let context = self.context()?;
-        let response: CallResponse = CallResponse::forward(&context.incoming_alkanes.clone());
+        if signer_key_0 != 0 || signer_key_1 != 0 {
+            self.signer_pointer()
+                .set(Arc::new(signature::signer_key(signer_key_0, signer_key_1)?));
+        }
+        let mut response: CallResponse = CallResponse::forward(&context.incoming_alkanes.clone());
+        response.alkanes.pay(self.deploy_auth_token(1)?);
         Ok(response)
     }
-}
 
-pub fn extract_witness_payload(tx: &Transaction) -> Option<Vec<u8>> {
-    // Try every input; Ordinals conventionally uses index 0, but
-    // looping covers edge‑cases.
-    for idx in 0..tx.input.len() {
-        if let Some(data) = find_witness_payload(&tx, idx) {
-            if !data.is_empty() {
-                return Some(data);
+    fn set_target_policy(&self, mode: u128) -> Result<CallResponse> {
+        TargetPolicy::set_target_policy(self, mode)
+    }
+
+    fn set_target_listed(&self, block: u128, tx: u128, listed: u128) -> Result<CallResponse> {
+        TargetPolicy::set_target_listed(self, block, tx, listed)
+    }
+
+    fn is_target_allowed(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        TargetPolicy::is_target_allowed(self, block, tx)
+    }
+
+    fn pause(&self) -> Result<CallResponse> {
+        Pausable::pause(self)
+    }
+
+    fn unpause(&self) -> Result<CallResponse> {
+        Pausable::unpause(self)
+    }
+
+    fn is_paused(&self) -> Result<CallResponse> {
+        Pausable::is_paused(self)
+    }
+
+    fn mint_auth_token(&self, amount: u128) -> Result<CallResponse> {
+        ProxyAdmin::mint_auth_token(self, amount)
+    }
+
+    fn transfer_ownership(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        ProxyAdmin::transfer_ownership(self, block, tx)
+    }
+
+    fn accept_ownership(&self) -> Result<CallResponse> {
+        ProxyAdmin::accept_ownership(self)
+    }
+
+    fn renounce_ownership(&self) -> Result<CallResponse> {
+        ProxyAdmin::renounce_ownership(self)
+    }
+
+    fn get_admin(&self) -> Result<CallResponse> {
+        ProxyAdmin::get_admin(self)
+    }
+
+    /// Runs every cellpack of a batch payload in order. The incoming alkanes
+    /// are sent to the first call only; the returned alkanes of all calls are
+    /// combined into the response, and their data is concatenated with each
+    /// entry prefixed by its length as a little endian u128.
+    fn execute_batch(&self) -> Result<CallResponse> {
+        self.execute(PayloadSource::Scan, ExecutionMode::Batch)
+    }
+
+    /// Runs a batch payload as a pipe: the alkanes returned by each call are
+    /// sent as the incoming alkanes of the next one, and only the alkanes
+    /// returned by the last call are paid out. Data is combined as in
+    /// `execute_batch`.
+    fn execute_pipe(&self) -> Result<CallResponse> {
+        self.execute(PayloadSource::Scan, ExecutionMode::Pipe)
+    }
+
+    /// Runs a batch payload without aborting on failing calls. The incoming
+    /// alkanes are sent to the first call only, and are refunded if it fails.
+    /// The response data starts with one `[success, error code, data length]`
+    /// entry per call, each value a little endian u128, followed by the data
+    /// of the successful calls in order.
+    fn execute_try(&self) -> Result<CallResponse> {
+        self.execute(PayloadSource::Scan, ExecutionMode::Try)
+    }
+
+    /// Reads the payload from the given envelope of the given input only,
+    /// instead of scanning for the first tagged envelope. The inputs are
+    /// `[input, envelope, mode]`, where `envelope` counts the envelopes within
+    /// that input, so `0` selects the first one, and `mode` selects how the
+    /// payload is run (see `ExecutionMode`). Both default to `0`.
+    fn execute_from_input(&self) -> Result<CallResponse> {
+        let (source, mode) = self.input_args()?;
+        self.execute(source, mode)
+    }
+
+    /// Reads `[input, envelope, mode]` following the opcode, where `envelope`
+    /// and `mode` may be left out and default to `0`.
+    fn input_args(&self) -> Result<(PayloadSource, ExecutionMode)> {
+        let context = self.context()?;
+        let args = &context.inputs[1..];
+        let input = *args
+            .first()
+            .ok_or_else(|| ProxyError::InvalidInput("missing input index".to_string()))?;
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        Ok((payload_source(input, arg(1))?, arg(2).try_into()?))
+    }
+
+    /// Runs the payload in the given mode, then checks the assertions it
+    /// carries against the alkanes about to be returned.
+    fn execute(&self, source: PayloadSource, mode: ExecutionMode) -> Result<CallResponse> {
+        if self.paused() {
+            return Ok(CallResponse::forward(&self.context()?.incoming_alkanes));
+        }
+        let context = self.context()?;
+        let WitnessPayload {
+            assertions, values, ..
+        } = self.read_payload(source, mode, true)?;
+        let response = match mode {
+            ExecutionMode::Single => {
+                self.call_target(&payload::decode_single(values)?, &context.incoming_alkanes)?
             }
+            ExecutionMode::Batch => self.run_cellpacks(&payload::decode_batch(values)?, false)?,
+            ExecutionMode::Pipe => self.run_cellpacks(&payload::decode_batch(values)?, true)?,
+            ExecutionMode::Try => self.try_cellpacks(&payload::decode_batch(values)?)?,
+        };
+        assertion::check_all(&assertions, &context.incoming_alkanes, &response.alkanes)?;
+        Ok(response)
+    }
+
+    fn run_cellpacks(&self, cellpacks: &[Cellpack], pipe: bool) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::default();
+        let mut incoming = context.incoming_alkanes.clone();
+        for cellpack in cellpacks.iter() {
+            let result = self.call_target(cellpack, &incoming)?;
+            incoming = if pipe {
+                result.alkanes.clone()
+            } else {
+                AlkaneTransferParcel::default()
+            };
+            combine_response(&mut response, result);
+        }
+        if pipe {
+            response.alkanes = incoming;
         }
+        Ok(response)
     }
-    None
-}
 
-impl AlkaneResponder for WitnessProxy {
-    fn fallback(&self) -> Result<CallResponse> {
+    fn try_cellpacks(&self, cellpacks: &[Cellpack]) -> Result<CallResponse> {
         let context = self.context()?;
+        let mut response = CallResponse::default();
+        let mut returned = Vec::new();
+        let mut incoming = context.incoming_alkanes.clone();
+        for cellpack in cellpacks.iter() {
+            let status = match self.call_target(cellpack, &incoming) {
+                Ok(result) => {
+                    let status = CallStatus::succeeded(result.data.len());
+                    response.alkanes.0.extend(result.alkanes.0);
+                    returned.extend(result.data);
+                    status
+                }
+                Err(e) => {
+                    response.alkanes.0.extend(incoming.0);
+                    CallStatus::failed(&e)
+                }
+            };
+            response.data.extend(status.encode());
+            incoming = AlkaneTransferParcel::default();
+        }
+        response.data.extend(returned);
+        Ok(response)
+    }
+
+    fn call_target(
+        &self,
+        cellpack: &Cellpack,
+        incoming: &AlkaneTransferParcel,
+    ) -> Result<CallResponse> {
+        self.check_target(&cellpack.target)?;
+        self.call(cellpack, incoming, self.fuel())
+            .map_err(|e| ProxyError::TargetCallFailed(e.to_string()).into())
+    }
+
+    /// Reads and authenticates the payload for a run in `mode`. The signer
+    /// nonce is only advanced when `consume` is set.
+    fn read_payload(
+        &self,
+        source: PayloadSource,
+        mode: ExecutionMode,
+        consume: bool,
+    ) -> Result<WitnessPayload> {
         let tx = self.transaction_object()?;
-        let witness_payload = match extract_witness_payload(&tx) {
-            Some(bytes) => bytes,
-            None => return Err(anyhow!("Failed to decode tx witness")),
+        let located = match source {
+            PayloadSource::Scan => locate_witness_payload(&tx),
+            PayloadSource::Input { input, envelope } => {
+                extract_witness_payload_at(&tx, input, envelope).map(|data| (input, data))
+            }
+        };
+        let (input, witness_payload) = match located {
+            Some(located) => located,
+            None => return Err(ProxyError::NoPayload.into()),
         };
-        let cellpack = decode_varint_list(&mut Cursor::new(witness_payload.clone()))?.try_into()?;
-        self.call(&cellpack, &context.incoming_alkanes, self.fuel())
+        let (header, body) = PayloadHeader::parse(&witness_payload)?;
+        let body = header.decode_body(body)?;
+        let signer = self.signer_pointer().get();
+        let cellpack_bytes = match (!signer.is_empty(), header.is_signed()) {
+            (false, false) => body,
+            (true, true) => {
+                let (sig, message) = signature::split_signed(&body)?;
+                signature::verify(
+                    &signer,
+                    &self.context()?.myself,
+                    mode as u8,
+                    header.flags,
+                    sig,
+                    message,
+                )?;
+                let (nonce, cellpack_bytes) = signature::split_nonce(message)?;
+                self.check_nonce(&signer, nonce, consume)?;
+                cellpack_bytes.to_vec()
+            }
+            (true, false) => {
+                return Err(ProxyError::Unauthorized(
+                    "this proxy only forwards signed payloads".to_string(),
+                )
+                .into())
+            }
+            (false, true) => {
+                return Err(ProxyError::Unauthorized(
+                    "signed payload sent to a proxy without a signer".to_string(),
+                )
+                .into())
+            }
+        };
+        let values = payload::decode_values(cellpack_bytes)?;
+        let (assertions, values) = if header.has_assertions() {
+            assertion::split_assertions(values)?
+        } else {
+            (Vec::new(), values)
+        };
+        Ok(WitnessPayload {
+            input,
+            assertions,
+            values,
+        })
+    }
+
+    fn signer_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/signer")
+    }
+
+    fn nonce_pointer(&self, signer: &Vec<u8>) -> StoragePointer {
+        StoragePointer::from_keyword("/nonces/").select(signer)
+    }
+
+    /// Accepts a signed payload only if it carries the signer's next nonce,
+    /// so every signed payload runs at most once.
+    fn check_nonce(&self, signer: &Vec<u8>, nonce: u128, consume: bool) -> Result<()> {
+        let mut pointer = self.nonce_pointer(signer);
+        let expected = pointer.get_value::<u128>();
+        if nonce != expected {
+            return Err(ProxyError::Unauthorized(format!(
+                "payload nonce {} does not match the expected nonce {}",
+                nonce, expected
+            ))
+            .into());
+        }
+        if consume {
+            pointer.set_value::<u128>(expected + 1);
+        }
+        Ok(())
+    }
+
+    /// Decodes the payload of the current transaction the way it would be
+    /// run in `mode` (see `ExecutionMode`), without calling anything. The
+    /// response data is `[input, count, ...assertions, count, ...cellpacks]`,
+    /// each value a little endian u128: the index of the input the payload
+    /// was found in, the assertions as `[kind, block, tx, amount]`, and the
+    /// cellpacks as `[len, block, tx, ...inputs]`, a single one in mode `0`.
+    /// Signed payloads are verified but their nonce is not consumed.
+    fn decode_payload(&self, mode: u128) -> Result<CallResponse> {
+        self.decode(PayloadSource::Scan, mode.try_into()?)
+    }
+
+    /// Like `decode_payload`, but reads the payload from the given envelope
+    /// of the given input, with the inputs `ExecuteFromInput` takes.
+    fn decode_payload_from_input(&self) -> Result<CallResponse> {
+        let (source, mode) = self.input_args()?;
+        self.decode(source, mode)
+    }
+
+    fn decode(&self, source: PayloadSource, mode: ExecutionMode) -> Result<CallResponse> {
+        let context = self.context()?;
+        let WitnessPayload {
+            input,
+            assertions,
+            values,
+        } = self.read_payload(source, mode, false)?;
+        let cellpacks = match mode {
+            ExecutionMode::Single => vec![payload::decode_single(values)?],
+            _ => payload::decode_batch(values)?,
+        };
+        let mut decoded = vec![input as u128, assertions.len() as u128];
+        for assertion in assertions.iter() {
+            decoded.extend(assertion.to_values());
+        }
+        decoded.push(cellpacks.len() as u128);
+        for cellpack in cellpacks.iter() {
+            decoded.push(cellpack.inputs.len() as u128 + 2);
+            decoded.extend([cellpack.target.block, cellpack.target.tx]);
+            decoded.extend(cellpack.inputs.iter());
+        }
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        for value in decoded {
+            response.data.extend(value.to_le_bytes());
+        }
+        Ok(response)
+    }
+
+    /// Returns the next nonce the given signer must use, as a little endian
+    /// u128.
+    fn get_nonce(&self, signer_key_0: u128, signer_key_1: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let signer = signature::signer_key(signer_key_0, signer_key_1)?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self
+            .nonce_pointer(&signer)
+            .get_value::<u128>()
+            .to_le_bytes()
+            .to_vec();
+        Ok(response)
+    }
+}
+
+/// Where in the transaction the payload is read from.
+#[derive(Clone, Copy)]
+enum PayloadSource {
+    /// The first tagged envelope of any input.
+    Scan,
+    /// The `envelope`-th envelope of input `input`.
+    Input { input: u32, envelope: u32 },
+}
+
+fn payload_source(input: u128, envelope: u128) -> Result<PayloadSource> {
+    let index = |value: u128, name: &str| {
+        u32::try_from(value).map_err(|_| {
+            ProxyError::InvalidInput(format!("{} index {} is out of range", name, value))
+        })
+    };
+    Ok(PayloadSource::Input {
+        input: index(input, "input")?,
+        envelope: index(envelope, "envelope")?,
+    })
+}
+
+/// An authenticated payload, split into its parts.
+struct WitnessPayload {
+    /// Index of the input the payload was found in.
+    input: u32,
+    /// Checked against the returned alkanes once the calls have run.
+    assertions: Vec<Assertion>,
+    /// The cellpack values, single or batch depending on the opcode.
+    values: Vec<u128>,
+}
+
+/// How the payload is run, as passed to `ExecuteFromInput`.
+#[derive(Clone, Copy)]
+enum ExecutionMode {
+    /// The payload is a single cellpack, as read by `fallback`.
+    Single = 0,
+    /// The payload is a batch, as read by `ExecuteBatch`.
+    Batch = 1,
+    /// The payload is a batch run as a pipe, as read by `ExecutePipe`.
+    Pipe = 2,
+    /// The payload is a batch whose calls may fail, as read by `ExecuteTry`.
+    Try = 3,
+}
+
+impl TryFrom<u128> for ExecutionMode {
+    type Error = anyhow::Error;
+
+    fn try_from(mode: u128) -> Result<Self> {
+        match mode {
+            0 => Ok(ExecutionMode::Single),
+            1 => Ok(ExecutionMode::Batch),
+            2 => Ok(ExecutionMode::Pipe),
+            3 => Ok(ExecutionMode::Try),
+            _ => Err(ProxyError::InvalidInput(format!("unknown execution mode {}", mode)).into()),
+        }
+    }
+}
+
+/// Outcome of one call of an `ExecuteTry` batch.
+struct CallStatus {
+    success: bool,
+    /// The `ProxyError` code of a failed call, `0` on success.
+    error_code: u32,
+    data_len: usize,
+}
+
+impl CallStatus {
+    fn succeeded(data_len: usize) -> Self {
+        CallStatus {
+            success: true,
+            error_code: 0,
+            data_len,
+        }
+    }
+
+    fn failed(error: &anyhow::Error) -> Self {
+        let error_code = match error.downcast_ref::<ProxyError>() {
+            Some(e) => e.code(),
+            None => ProxyError::TargetCallFailed(String::new()).code(),
+        };
+        CallStatus {
+            success: false,
+            error_code,
+            data_len: 0,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut data = (self.success as u128).to_le_bytes().to_vec();
+        data.extend((self.error_code as u128).to_le_bytes());
+        data.extend((self.data_len as u128).to_le_bytes());
+        data
+    }
+}
+
+fn combine_response(response: &mut CallResponse, result: CallResponse) {
+    response.alkanes.0.extend(result.alkanes.0);
+    response
+        .data
+        .extend((result.data.len() as u128).to_le_bytes());
+    response.data.extend(result.data);
+}
+
+impl AlkaneResponder for WitnessProxy {
+    fn fallback(&self) -> Result<CallResponse> {
+        self.execute(PayloadSource::Scan, ExecutionMode::Single)
     }
 }
 // Use the new macro format
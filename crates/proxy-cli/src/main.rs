@@ -0,0 +1,181 @@
+use alkanes_proxies::client::diesel::diesel_inputs;
+use alkanes_proxies::client::payload::{encode_payload, envelope_script};
+#[cfg(feature = "simulator")]
+use alkanes_proxies::simulator::{default_fixtures, Simulator};
+use alkanes_proxy_support::assertion::split_assertions;
+use alkanes_proxy_support::payload::{self, PayloadHeader};
+use alkanes_proxy_support::signature::{split_nonce, split_signed};
+use alkanes_proxy_support::witness::locate_witness_payload;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use bitcoin::consensus::deserialize;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::Transaction;
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// Height the fixtures are indexed at; the simulated transaction goes in the
+/// block after it.
+#[cfg(feature = "simulator")]
+const DEFAULT_HEIGHT: u32 = 880_000;
+
+const USAGE: &str = "usage:
+  proxy-cli encode [--envelope <x-only key hex>] <block> <tx> [inputs...]
+      prints the witness proxy payload hex, or with --envelope the reveal
+      tapscript hex carrying it
+  proxy-cli decode <tx hex>
+      prints the first witness proxy payload of the transaction and the
+      cellpack it decodes to
+  proxy-cli diesel <block> <tx> [inputs...]
+      prints the diesel proxy input vector [block, tx, ...inputs]
+  proxy-cli simulate [--height <height>] <tx hex>
+      indexes the transaction against a local state with both proxies
+      deployed at their signet addresses, and prints the protostone traces
+      and the balance sheets of its outputs; needs the simulator feature";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => print!("{}", output),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs a command and returns what it prints.
+fn run(args: &[String]) -> Result<String> {
+    match args.split_first() {
+        Some((command, rest)) if command == "encode" => encode(rest),
+        Some((command, rest)) if command == "decode" => decode(rest),
+        Some((command, rest)) if command == "diesel" => diesel(rest),
+        Some((command, rest)) if command == "simulate" => simulate(rest),
+        _ => Err(anyhow!("{}", USAGE)),
+    }
+}
+
+fn encode(args: &[String]) -> Result<String> {
+    let (key, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--envelope" => {
+            let (key, rest) = rest
+                .split_first()
+                .ok_or_else(|| anyhow!("--envelope takes an x-only key"))?;
+            (Some(XOnlyPublicKey::from_slice(&hex::decode(key)?)?), rest)
+        }
+        _ => (None, args),
+    };
+    let payload = encode_payload(&parse_cellpack(args)?);
+    let bytes = match key {
+        Some(key) => envelope_script(&key, &payload)?.into_bytes(),
+        None => payload,
+    };
+    Ok(format!("{}\n", hex::encode(bytes)))
+}
+
+fn decode(args: &[String]) -> Result<String> {
+    let [tx_hex] = args else {
+        return Err(anyhow!("decode takes a single transaction hex"));
+    };
+    let tx: Transaction = deserialize(&hex::decode(tx_hex.trim())?)?;
+    let (input, bytes) = locate_witness_payload(&tx)
+        .ok_or_else(|| anyhow!("transaction carries no witness proxy payload"))?;
+    let mut out = String::new();
+    writeln!(out, "input: {}", input)?;
+    writeln!(out, "payload: {}", hex::encode(&bytes))?;
+    let (header, body) = PayloadHeader::parse(&bytes)?;
+    writeln!(out, "version: {}", header.version)?;
+    writeln!(out, "flags: {:#04x}", header.flags)?;
+    let body = header.decode_body(body)?;
+    let cellpack_bytes = if header.is_signed() {
+        let (signature, message) = split_signed(&body)?;
+        let (nonce, cellpack_bytes) = split_nonce(message)?;
+        writeln!(out, "signature: {}", hex::encode(signature))?;
+        writeln!(out, "nonce: {}", nonce)?;
+        cellpack_bytes.to_vec()
+    } else {
+        body
+    };
+    let mut values = payload::decode_values(cellpack_bytes)?;
+    if header.has_assertions() {
+        let (assertions, rest) = split_assertions(values)?;
+        for assertion in assertions.iter() {
+            writeln!(out, "assertion: {:?}", assertion)?;
+        }
+        values = rest;
+    }
+    writeln!(out, "values: {:?}", values)?;
+    // Single and batch payloads share a layout; which one applies depends on
+    // the opcode the proxy is called with
+    match payload::decode_single(values) {
+        Ok(cellpack) => {
+            writeln!(
+                out,
+                "target: {}:{}",
+                cellpack.target.block, cellpack.target.tx
+            )?;
+            writeln!(out, "inputs: {:?}", cellpack.inputs)?;
+        }
+        Err(e) => writeln!(out, "not a single cellpack: {}", e)?,
+    }
+    Ok(out)
+}
+
+fn diesel(args: &[String]) -> Result<String> {
+    let inputs = diesel_inputs(&parse_cellpack(args)?);
+    Ok(format!(
+        "{}\n",
+        inputs
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    ))
+}
+
+#[cfg(feature = "simulator")]
+fn simulate(args: &[String]) -> Result<String> {
+    let (height, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--height" => {
+            let (height, rest) = rest
+                .split_first()
+                .ok_or_else(|| anyhow!("--height takes a block height"))?;
+            (height.parse::<u32>()?, rest)
+        }
+        _ => (DEFAULT_HEIGHT, args),
+    };
+    let [tx_hex] = args else {
+        return Err(anyhow!("simulate takes a single transaction hex"));
+    };
+    let tx: Transaction = deserialize(&hex::decode(tx_hex.trim())?)?;
+    let mut simulator = Simulator::new(height, &default_fixtures())?;
+    Ok(simulator.simulate(&tx)?.to_string())
+}
+
+#[cfg(not(feature = "simulator"))]
+fn simulate(_args: &[String]) -> Result<String> {
+    Err(anyhow!(
+        "simulate needs proxy-cli built with the simulator feature"
+    ))
+}
+
+/// Reads `<block> <tx> [inputs...]`, each a decimal u128.
+fn parse_cellpack(args: &[String]) -> Result<Cellpack> {
+    let values = args
+        .iter()
+        .map(|arg| {
+            arg.parse::<u128>()
+                .map_err(|_| anyhow!("{} is not a u128", arg))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if values.len() < 2 {
+        return Err(anyhow!("a call needs at least a target block and tx"));
+    }
+    Ok(Cellpack {
+        target: AlkaneId::new(values[0], values[1]),
+        inputs: values[2..].to_vec(),
+    })
+}
@@ -0,0 +1,144 @@
+use crate::{decode, diesel, encode};
+use alkanes_proxies::client::payload::{encode_payload, encode_signed_values, envelope_script};
+use alkanes_proxies::client::SIGNET_WITNESS_PROXY;
+use alkanes_proxy_support::payload::{FLAG_SIGNED, SIGNATURE_LEN};
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::serialize;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::{Keypair, Secp256k1, XOnlyPublicKey};
+use bitcoin::transaction::Version;
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, Txid, Witness};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn cellpack() -> Cellpack {
+    Cellpack {
+        target: AlkaneId { block: 2, tx: 1 },
+        inputs: vec![22, 1000],
+    }
+}
+
+fn key() -> Result<XOnlyPublicKey> {
+    let secp = Secp256k1::new();
+    Ok(Keypair::from_seckey_slice(&secp, &[1u8; 32])?
+        .x_only_public_key()
+        .0)
+}
+
+/// The hex of a transaction whose second input reveals `payload`.
+fn reveal_hex(payload: &[u8]) -> Result<String> {
+    let mut witness = Witness::new();
+    witness.push(envelope_script(&key()?, payload)?.as_bytes());
+    witness.push(Vec::<u8>::new());
+    let input = |witness| TxIn {
+        previous_output: OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 0,
+        },
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness,
+    };
+    let tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![input(Witness::new()), input(witness)],
+        output: Vec::new(),
+    };
+    Ok(hex::encode(serialize(&tx)))
+}
+
+#[wasm_bindgen_test]
+fn test_encode() -> Result<()> {
+    let payload = encode_payload(&cellpack());
+    assert_eq!(
+        encode(&args(&["2", "1", "22", "1000"]))?,
+        format!("{}\n", hex::encode(&payload))
+    );
+
+    let key = key()?;
+    assert_eq!(
+        encode(&args(&[
+            "--envelope",
+            &key.to_string(),
+            "2",
+            "1",
+            "22",
+            "1000"
+        ]))?,
+        format!(
+            "{}\n",
+            hex::encode(envelope_script(&key, &payload)?.as_bytes())
+        )
+    );
+
+    assert!(encode(&args(&["2"])).is_err());
+    assert!(encode(&args(&["--envelope"])).is_err());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_decode() -> Result<()> {
+    let payload = encode_payload(&cellpack());
+    assert_eq!(
+        decode(&args(&[&reveal_hex(&payload)?]))?,
+        format!(
+            "input: 1\n\
+             payload: {}\n\
+             version: 1\n\
+             flags: 0x00\n\
+             values: [2, 1, 22, 1000]\n\
+             target: 2:1\n\
+             inputs: [22, 1000]\n",
+            hex::encode(&payload)
+        )
+    );
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_decode_signed() -> Result<()> {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32])?;
+    let payload = encode_signed_values(
+        &secp,
+        &keypair,
+        &SIGNET_WITNESS_PROXY,
+        0,
+        7,
+        &[2, 1, 22, 1000],
+        0,
+    );
+    let output = decode(&args(&[&reveal_hex(&payload)?]))?;
+    let signature = output
+        .lines()
+        .find_map(|line| line.strip_prefix("signature: "))
+        .expect("signature is printed");
+    assert_eq!(signature.len(), SIGNATURE_LEN * 2);
+    assert!(output.contains(&format!("flags: {:#04x}\n", FLAG_SIGNED)));
+    assert!(output.contains("nonce: 7\n"));
+    assert!(output.contains("values: [2, 1, 22, 1000]\n"));
+    assert!(output.contains("target: 2:1\n"));
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_decode_rejects_transaction_without_payload() -> Result<()> {
+    assert!(decode(&args(&[&reveal_hex(b"not a proxy payload")?])).is_err());
+    assert!(decode(&args(&[])).is_err());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel() -> Result<()> {
+    assert_eq!(diesel(&args(&["2", "1", "22", "1000"]))?, "2,1,22,1000\n");
+    assert_eq!(diesel(&args(&["2", "1"]))?, "2,1\n");
+    assert!(diesel(&args(&["2", "x"])).is_err());
+    Ok(())
+}
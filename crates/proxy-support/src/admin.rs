@@ -0,0 +1,180 @@
+use crate::ProxyError;
+use alkanes_runtime::auth::AuthenticatedResponder;
+use alkanes_runtime::storage::StoragePointer;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::parcel::AlkaneTransfer;
+use alkanes_support::response::CallResponse;
+use anyhow::Result;
+use metashrew_support::index_pointer::KeyValuePointer;
+use std::sync::Arc;
+
+/// Who administers a proxy deployment, as reported by the admin view opcodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdminState {
+    /// The auth token that currently guards the admin opcodes.
+    pub auth_token: Option<AlkaneId>,
+    /// The alkane proposed as the next auth token, if a handoff is pending.
+    pub pending: Option<AlkaneId>,
+    /// Whether admin rights were given up for good.
+    pub renounced: bool,
+}
+
+impl AdminState {
+    /// `[auth block, auth tx, pending block, pending tx, renounced]`, each a
+    /// little endian u128, with zeros for ids that are not set.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for id in [self.auth_token, self.pending] {
+            let id = id.unwrap_or_default();
+            data.extend(id.block.to_le_bytes());
+            data.extend(id.tx.to_le_bytes());
+        }
+        data.extend((self.renounced as u128).to_le_bytes());
+        data
+    }
+}
+
+/// Admin opcodes of the proxies are guarded by the auth token minted at
+/// initialization. Ownership changes are recorded under `/auth`,
+/// `/admin/pending` and `/admin/renounced` so indexers can tell who
+/// administers a deployment.
+pub trait ProxyAdmin: AuthenticatedResponder {
+    fn auth_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/auth")
+    }
+
+    fn pending_admin_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/admin/pending")
+    }
+
+    fn renounced_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/admin/renounced")
+    }
+
+    /// Fails unless the auth token is among the incoming alkanes and admin
+    /// rights have not been renounced.
+    fn require_admin(&self) -> Result<()> {
+        if self.renounced_pointer().get_value::<u8>() == 1 {
+            return Err(ProxyError::NotAdmin.into());
+        }
+        let context = self.context()?;
+        let auth_token = self.auth_token().map_err(|_| ProxyError::NotAdmin)?;
+        if !context
+            .incoming_alkanes
+            .0
+            .iter()
+            .any(|transfer| transfer.id == auth_token)
+        {
+            return Err(ProxyError::NotAdmin.into());
+        }
+        Ok(())
+    }
+
+    /// Mints `units` of a fresh auth token and makes it the only one that
+    /// guards the admin opcodes. The previous token stops working.
+    fn rotate_auth_token(&self, units: u128) -> Result<AlkaneTransfer> {
+        self.require_admin()?;
+        let token = self.deploy_auth_token(units)?;
+        self.auth_pointer().set(Arc::new(token.id.into()));
+        self.pending_admin_pointer().set(Arc::new(Vec::new()));
+        Ok(token)
+    }
+
+    /// First step of a handoff: proposes `next` as the auth token. Nothing
+    /// changes until its holder accepts.
+    fn propose_admin(&self, next: &AlkaneId) -> Result<()> {
+        self.require_admin()?;
+        self.pending_admin_pointer().set(Arc::new((*next).into()));
+        Ok(())
+    }
+
+    /// Second step of a handoff: the proposed alkane must be among the
+    /// incoming alkanes, and becomes the auth token.
+    fn accept_admin(&self) -> Result<()> {
+        if self.renounced_pointer().get_value::<u8>() == 1 {
+            return Err(ProxyError::NotAdmin.into());
+        }
+        let pending = self.admin_state().pending.ok_or(ProxyError::NotAdmin)?;
+        let context = self.context()?;
+        if !context
+            .incoming_alkanes
+            .0
+            .iter()
+            .any(|transfer| transfer.id == pending)
+        {
+            return Err(ProxyError::NotAdmin.into());
+        }
+        self.auth_pointer().set(Arc::new(pending.into()));
+        self.pending_admin_pointer().set(Arc::new(Vec::new()));
+        Ok(())
+    }
+
+    /// Gives up admin rights for good: every admin opcode fails afterwards.
+    fn renounce_admin(&self) -> Result<()> {
+        self.require_admin()?;
+        self.pending_admin_pointer().set(Arc::new(Vec::new()));
+        self.renounced_pointer().set_value::<u8>(1);
+        Ok(())
+    }
+
+    fn admin_state(&self) -> AdminState {
+        let read = |pointer: StoragePointer| -> Option<AlkaneId> {
+            let bytes = pointer.get();
+            if bytes.is_empty() {
+                None
+            } else {
+                bytes.as_ref().clone().try_into().ok()
+            }
+        };
+        AdminState {
+            auth_token: read(self.auth_pointer()),
+            pending: read(self.pending_admin_pointer()),
+            renounced: self.renounced_pointer().get_value::<u8>() == 1,
+        }
+    }
+
+    /// Opcode handler minting `amount` units of a fresh auth token to the
+    /// caller, which becomes the only token that guards the admin opcodes.
+    /// Requires the current auth token, which is returned but no longer
+    /// grants admin rights.
+    fn mint_auth_token(&self, amount: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let token = self.rotate_auth_token(amount)?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.pay(token);
+        Ok(response)
+    }
+
+    /// Opcode handler proposing the alkane `block:tx` as the next auth token.
+    /// Requires the current auth token; the handoff completes once the
+    /// holder of the proposed alkane calls `accept_ownership` with it.
+    fn transfer_ownership(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.propose_admin(&AlkaneId::new(block, tx))?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler completing a pending handoff. The proposed alkane must
+    /// be sent along, and is returned.
+    fn accept_ownership(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.accept_admin()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler giving up admin rights for good. Requires the auth
+    /// token.
+    fn renounce_ownership(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.renounce_admin()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler returning the `AdminState` encoding.
+    fn get_admin(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self.admin_state().encode();
+        Ok(response)
+    }
+}
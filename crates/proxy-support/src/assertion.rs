@@ -0,0 +1,114 @@
+use crate::ProxyError;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::parcel::AlkaneTransferParcel;
+use anyhow::Result;
+
+/// A condition on the alkanes a proxy call returns, checked after every
+/// sub-call has run. A failing assertion reverts the whole call, which
+/// gives slippage protection even when the target has no minimum-out
+/// argument of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assertion {
+    /// At least `amount` of `id` must be among the returned alkanes.
+    MinReturned { id: AlkaneId, amount: u128 },
+    /// No more than `amount` of the incoming `id` may be kept by the calls,
+    /// that is incoming minus returned.
+    MaxConsumed { id: AlkaneId, amount: u128 },
+}
+
+impl Assertion {
+    /// `[kind, block, tx, amount]`, where `kind` is `0` for `MinReturned`
+    /// and `1` for `MaxConsumed`.
+    pub const LEN: usize = 4;
+
+    pub fn to_values(&self) -> [u128; 4] {
+        match self {
+            Assertion::MinReturned { id, amount } => [0, id.block, id.tx, *amount],
+            Assertion::MaxConsumed { id, amount } => [1, id.block, id.tx, *amount],
+        }
+    }
+
+    fn from_values(values: &[u128]) -> Result<Assertion> {
+        let id = AlkaneId::new(values[1], values[2]);
+        let amount = values[3];
+        match values[0] {
+            0 => Ok(Assertion::MinReturned { id, amount }),
+            1 => Ok(Assertion::MaxConsumed { id, amount }),
+            kind => {
+                Err(ProxyError::MalformedPayload(format!("unknown assertion kind {}", kind)).into())
+            }
+        }
+    }
+
+    /// Checks the assertion against what the proxy received and what it is
+    /// about to return.
+    pub fn check(
+        &self,
+        incoming: &AlkaneTransferParcel,
+        returned: &AlkaneTransferParcel,
+    ) -> Result<()> {
+        match self {
+            Assertion::MinReturned { id, amount } => {
+                let got = total(returned, id);
+                if got < *amount {
+                    return Err(ProxyError::AssertionFailed(format!(
+                        "returned {} of {}:{}, expected at least {}",
+                        got, id.block, id.tx, amount
+                    ))
+                    .into());
+                }
+            }
+            Assertion::MaxConsumed { id, amount } => {
+                let consumed = total(incoming, id).saturating_sub(total(returned, id));
+                if consumed > *amount {
+                    return Err(ProxyError::AssertionFailed(format!(
+                        "consumed {} of {}:{}, expected at most {}",
+                        consumed, id.block, id.tx, amount
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits the assertions section off the front of the payload values:
+/// `[count, ...assertions, ...cellpack values]`.
+pub fn split_assertions(values: Vec<u128>) -> Result<(Vec<Assertion>, Vec<u128>)> {
+    let (count, rest) = values
+        .split_first()
+        .ok_or_else(|| ProxyError::MalformedPayload("assertions section is missing".to_string()))?;
+    let len = usize::try_from(*count)
+        .ok()
+        .and_then(|count| count.checked_mul(Assertion::LEN))
+        .filter(|len| *len <= rest.len())
+        .ok_or_else(|| {
+            ProxyError::MalformedPayload("assertions section is truncated".to_string())
+        })?;
+    let assertions = rest[..len]
+        .chunks(Assertion::LEN)
+        .map(Assertion::from_values)
+        .collect::<Result<Vec<_>>>()?;
+    Ok((assertions, rest[len..].to_vec()))
+}
+
+/// Checks every assertion, failing on the first one that does not hold.
+pub fn check_all(
+    assertions: &[Assertion],
+    incoming: &AlkaneTransferParcel,
+    returned: &AlkaneTransferParcel,
+) -> Result<()> {
+    for assertion in assertions.iter() {
+        assertion.check(incoming, returned)?;
+    }
+    Ok(())
+}
+
+fn total(parcel: &AlkaneTransferParcel, id: &AlkaneId) -> u128 {
+    parcel
+        .0
+        .iter()
+        .filter(|transfer| transfer.id == *id)
+        .fold(0u128, |sum, transfer| sum.saturating_add(transfer.value))
+}
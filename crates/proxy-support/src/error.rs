@@ -0,0 +1,99 @@
+use std::fmt;
+
+/// Marker every proxy revert message starts with, followed by the numeric
+/// error code. The runtime may put other bytes in front of the message, so
+/// decoders should search for it rather than expect it at offset zero.
+pub const ERROR_PREFIX: &str = "PROXY_ERROR ";
+
+/// Failures shared by the proxy contracts. The numeric codes are part of the
+/// revert data and must never be reassigned; new variants take new codes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyError {
+    /// The transaction carries no envelope the proxy can read.
+    NoPayload,
+    /// The payload is not a valid varint list.
+    MalformedVarints(String),
+    /// The inputs do not contain a target `AlkaneId`.
+    CellpackTooShort,
+    /// The proxied call reverted.
+    TargetCallFailed(String),
+    /// The mint call succeeded but returned no alkanes.
+    MintReturnedNothing,
+    /// The payload header or layout is invalid or unsupported.
+    MalformedPayload(String),
+    /// The payload is not authorized by the registered signer.
+    Unauthorized(String),
+    /// The target policy forbids calling this `AlkaneId`.
+    TargetForbidden(String),
+    /// An admin opcode was called without the auth token.
+    NotAdmin,
+    /// A post-call assertion carried in the payload does not hold.
+    AssertionFailed(String),
+    /// An opcode input is out of range, such as an unknown mode.
+    InvalidInput(String),
+    /// The proxy lacks configuration the call needs.
+    NotConfigured(String),
+    /// A mint call reverted.
+    MintFailed(String),
+}
+
+impl ProxyError {
+    pub fn code(&self) -> u32 {
+        match self {
+            ProxyError::NoPayload => 1,
+            ProxyError::MalformedVarints(_) => 2,
+            ProxyError::CellpackTooShort => 3,
+            ProxyError::TargetCallFailed(_) => 4,
+            ProxyError::MintReturnedNothing => 5,
+            ProxyError::MalformedPayload(_) => 6,
+            ProxyError::Unauthorized(_) => 7,
+            ProxyError::TargetForbidden(_) => 8,
+            ProxyError::NotAdmin => 9,
+            ProxyError::AssertionFailed(_) => 10,
+            ProxyError::InvalidInput(_) => 11,
+            ProxyError::NotConfigured(_) => 12,
+            ProxyError::MintFailed(_) => 13,
+        }
+    }
+
+    /// Extracts the error code from the revert data of a failed proxy call,
+    /// if the failure came from a proxy.
+    pub fn code_from_revert(data: &[u8]) -> Option<u32> {
+        let prefix = ERROR_PREFIX.as_bytes();
+        let start = data
+            .windows(prefix.len())
+            .position(|window| window == prefix)?
+            + prefix.len();
+        let digits = data[start..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .map(|byte| *byte as char)
+            .collect::<String>();
+        digits.parse().ok()
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}: ", ERROR_PREFIX, self.code())?;
+        match self {
+            ProxyError::NoPayload => write!(f, "no witness proxy payload in transaction"),
+            ProxyError::MalformedVarints(e) => write!(f, "malformed varints: {}", e),
+            ProxyError::CellpackTooShort => write!(f, "cellpack is shorter than a target id"),
+            ProxyError::TargetCallFailed(e) => write!(f, "target call failed: {}", e),
+            ProxyError::MintReturnedNothing => write!(f, "mint returned no alkanes"),
+            ProxyError::MalformedPayload(e) => write!(f, "malformed payload: {}", e),
+            ProxyError::Unauthorized(e) => write!(f, "unauthorized payload: {}", e),
+            ProxyError::TargetForbidden(target) => {
+                write!(f, "target {} is forbidden by the proxy policy", target)
+            }
+            ProxyError::NotAdmin => write!(f, "auth token is not in incoming alkanes"),
+            ProxyError::AssertionFailed(e) => write!(f, "assertion failed: {}", e),
+            ProxyError::InvalidInput(e) => write!(f, "invalid input: {}", e),
+            ProxyError::NotConfigured(e) => write!(f, "not configured: {}", e),
+            ProxyError::MintFailed(e) => write!(f, "mint failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
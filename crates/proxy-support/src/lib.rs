@@ -0,0 +1,10 @@
+pub mod admin;
+pub mod assertion;
+pub mod error;
+pub mod pause;
+pub mod payload;
+pub mod policy;
+pub mod signature;
+pub mod witness;
+
+pub use error::ProxyError;
@@ -0,0 +1,47 @@
+use crate::admin::ProxyAdmin;
+use alkanes_runtime::storage::StoragePointer;
+use alkanes_support::response::CallResponse;
+use anyhow::Result;
+use metashrew_support::index_pointer::KeyValuePointer;
+
+/// Emergency switch that stops a proxy from forwarding calls. While paused,
+/// forwarding opcodes refund the incoming alkanes instead of calling out.
+pub trait Pausable: ProxyAdmin {
+    fn paused_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/paused")
+    }
+
+    fn paused(&self) -> bool {
+        self.paused_pointer().get_value::<u8>() == 1
+    }
+
+    fn store_paused(&self, paused: bool) -> Result<()> {
+        self.require_admin()?;
+        self.paused_pointer().set_value::<u8>(paused as u8);
+        Ok(())
+    }
+
+    /// Opcode handler that stops the proxy from forwarding calls; incoming
+    /// alkanes are refunded until it is unpaused. Requires the auth token.
+    fn pause(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.store_paused(true)?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler that resumes forwarding calls. Requires the auth token.
+    fn unpause(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.store_paused(false)?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler returning a single byte, `1` if the proxy is paused and
+    /// `0` otherwise.
+    fn is_paused(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data.push(self.paused() as u8);
+        Ok(response)
+    }
+}
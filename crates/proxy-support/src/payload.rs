@@ -0,0 +1,189 @@
+use crate::ProxyError;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use flate2::read::{DeflateDecoder, GzDecoder};
+use protorune_support::utils::decode_varint_list;
+use std::io::{Cursor, Read};
+
+/// Tag every witness proxy payload starts with, so that unrelated envelopes
+/// (ordinal inscriptions, contract deployments) are never read as calls.
+pub const PAYLOAD_MAGIC: [u8; 4] = *b"WPXY";
+
+/// The only payload format version this proxy understands.
+pub const PAYLOAD_VERSION: u8 = 1;
+
+/// The payload body is a raw deflate stream.
+pub const FLAG_DEFLATE: u8 = 0x01;
+
+/// The payload body is a gzip stream.
+pub const FLAG_GZIP: u8 = 0x02;
+
+/// The body is a BIP340 signature followed by the signed cellpack bytes.
+/// Signing happens before compression, so the signature is carried inside
+/// the compressed stream.
+pub const FLAG_SIGNED: u8 = 0x04;
+
+/// The varint values start with a section of post-call assertions, see
+/// `assertion::split_assertions`.
+pub const FLAG_ASSERTIONS: u8 = 0x08;
+
+/// Flag bits this version understands. Unknown bits are rejected so that a
+/// payload meant for a newer encoding is never misread by an older proxy.
+pub const SUPPORTED_FLAGS: u8 = FLAG_DEFLATE | FLAG_GZIP | FLAG_SIGNED | FLAG_ASSERTIONS;
+
+/// Length of the BIP340 signature a signed body starts with.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Length of the little endian signer nonce following the signature.
+pub const NONCE_LEN: usize = 16;
+
+/// Upper bound on the size of a decompressed body, so that a small witness
+/// cannot expand into a memory bomb inside the proxy.
+pub const MAX_DECOMPRESSED_LEN: usize = 128 * 1024;
+
+/// `magic || version || flags`, followed by the payload body.
+pub const HEADER_LEN: usize = PAYLOAD_MAGIC.len() + 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayloadHeader {
+    pub version: u8,
+    pub flags: u8,
+}
+
+impl PayloadHeader {
+    /// Whether the envelope bytes carry the proxy magic at all.
+    pub fn is_tagged(bytes: &[u8]) -> bool {
+        bytes.len() >= HEADER_LEN && bytes[..PAYLOAD_MAGIC.len()] == PAYLOAD_MAGIC
+    }
+
+    /// Splits tagged envelope bytes into the header and the payload body,
+    /// rejecting versions and flags this proxy does not understand.
+    pub fn parse(bytes: &[u8]) -> Result<(PayloadHeader, &[u8])> {
+        if !Self::is_tagged(bytes) {
+            return Err(ProxyError::MalformedPayload(
+                "payload does not carry the witness proxy header".to_string(),
+            )
+            .into());
+        }
+        let header = PayloadHeader {
+            version: bytes[PAYLOAD_MAGIC.len()],
+            flags: bytes[PAYLOAD_MAGIC.len() + 1],
+        };
+        if header.version != PAYLOAD_VERSION {
+            return Err(ProxyError::MalformedPayload(format!(
+                "unsupported payload version {}",
+                header.version
+            ))
+            .into());
+        }
+        if header.flags & !SUPPORTED_FLAGS != 0 {
+            return Err(ProxyError::MalformedPayload(format!(
+                "unsupported payload flags {:#04x}",
+                header.flags
+            ))
+            .into());
+        }
+        if header.flags & FLAG_DEFLATE != 0 && header.flags & FLAG_GZIP != 0 {
+            return Err(ProxyError::MalformedPayload(
+                "payload cannot be both deflate and gzip compressed".to_string(),
+            )
+            .into());
+        }
+        Ok((header, &bytes[HEADER_LEN..]))
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.flags & FLAG_SIGNED != 0
+    }
+
+    pub fn has_assertions(&self) -> bool {
+        self.flags & FLAG_ASSERTIONS != 0
+    }
+
+    /// Returns the payload body, decompressed if the header says so.
+    pub fn decode_body(&self, body: &[u8]) -> Result<Vec<u8>> {
+        if self.flags & FLAG_DEFLATE != 0 {
+            inflate(DeflateDecoder::new(body))
+        } else if self.flags & FLAG_GZIP != 0 {
+            inflate(GzDecoder::new(body))
+        } else {
+            Ok(body.to_vec())
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = PAYLOAD_MAGIC.to_vec();
+        bytes.push(self.version);
+        bytes.push(self.flags);
+        bytes
+    }
+}
+
+fn inflate<R: Read>(decoder: R) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    decoder
+        .take(MAX_DECOMPRESSED_LEN as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| {
+            ProxyError::MalformedPayload(format!("failed to decompress payload: {}", e))
+        })?;
+    if body.len() > MAX_DECOMPRESSED_LEN {
+        return Err(ProxyError::MalformedPayload(format!(
+            "decompressed payload exceeds {} bytes",
+            MAX_DECOMPRESSED_LEN
+        ))
+        .into());
+    }
+    Ok(body)
+}
+
+/// Decodes the cellpack bytes of a payload body into its varint values.
+pub fn decode_values(bytes: Vec<u8>) -> Result<Vec<u128>> {
+    decode_varint_list(&mut Cursor::new(bytes))
+        .map_err(|e| ProxyError::MalformedVarints(e.to_string()).into())
+}
+
+/// Reads a single cellpack payload: `[block, tx, ...inputs]`.
+pub fn decode_single(values: Vec<u128>) -> Result<Cellpack> {
+    if values.len() < 2 {
+        return Err(ProxyError::CellpackTooShort.into());
+    }
+    Ok(Cellpack {
+        target: AlkaneId::new(values[0], values[1]),
+        inputs: values[2..].to_vec(),
+    })
+}
+
+/// Splits a batch payload into its cellpacks.
+///
+/// A batch is a flat varint list where every cellpack is prefixed with the
+/// number of values it spans (target block, target tx and inputs):
+/// `[len_0, block_0, tx_0, ...inputs_0, len_1, block_1, tx_1, ...inputs_1, ...]`
+pub fn decode_batch(values: Vec<u128>) -> Result<Vec<Cellpack>> {
+    let mut cellpacks = Vec::new();
+    let mut rest = values.as_slice();
+    while let Some((len, tail)) = rest.split_first() {
+        let len = usize::try_from(*len)
+            .map_err(|_| ProxyError::MalformedPayload("batch entry length overflow".to_string()))?;
+        if len < 2 {
+            return Err(ProxyError::CellpackTooShort.into());
+        }
+        if len > tail.len() {
+            return Err(ProxyError::MalformedPayload(format!(
+                "batch entry {} is truncated",
+                cellpacks.len()
+            ))
+            .into());
+        }
+        cellpacks.push(Cellpack {
+            target: AlkaneId::new(tail[0], tail[1]),
+            inputs: tail[2..len].to_vec(),
+        });
+        rest = &tail[len..];
+    }
+    if cellpacks.is_empty() {
+        return Err(ProxyError::MalformedPayload("batch payload is empty".to_string()).into());
+    }
+    Ok(cellpacks)
+}
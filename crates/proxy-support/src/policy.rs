@@ -0,0 +1,109 @@
+use crate::admin::ProxyAdmin;
+use crate::ProxyError;
+use alkanes_runtime::storage::StoragePointer;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::response::CallResponse;
+use anyhow::Result;
+use metashrew_support::index_pointer::KeyValuePointer;
+
+/// Which targets a proxy may call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyMode {
+    /// Every target may be called.
+    Open = 0,
+    /// Only listed targets may be called.
+    Allowlist = 1,
+    /// Every target but the listed ones may be called.
+    Denylist = 2,
+}
+
+impl TryFrom<u128> for PolicyMode {
+    type Error = anyhow::Error;
+
+    fn try_from(mode: u128) -> Result<Self> {
+        match mode {
+            0 => Ok(PolicyMode::Open),
+            1 => Ok(PolicyMode::Allowlist),
+            2 => Ok(PolicyMode::Denylist),
+            _ => Err(ProxyError::InvalidInput(format!("unknown policy mode {}", mode)).into()),
+        }
+    }
+}
+
+/// An allowlist or denylist of target `AlkaneId`s kept in contract storage.
+/// A single list backs both modes, so switching modes reinterprets it.
+pub trait TargetPolicy: ProxyAdmin {
+    fn policy_mode_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword("/policy/mode")
+    }
+
+    fn policy_target_pointer(&self, target: &AlkaneId) -> StoragePointer {
+        StoragePointer::from_keyword("/policy/targets/").select(&(*target).into())
+    }
+
+    fn policy_mode(&self) -> Result<PolicyMode> {
+        self.policy_mode_pointer().get_value::<u128>().try_into()
+    }
+
+    fn store_policy_mode(&self, mode: u128) -> Result<()> {
+        self.require_admin()?;
+        let mode = PolicyMode::try_from(mode)?;
+        self.policy_mode_pointer().set_value::<u128>(mode as u128);
+        Ok(())
+    }
+
+    fn store_target_listed(&self, target: &AlkaneId, listed: bool) -> Result<()> {
+        self.require_admin()?;
+        self.policy_target_pointer(target)
+            .set_value::<u8>(listed as u8);
+        Ok(())
+    }
+
+    fn target_listed(&self, target: &AlkaneId) -> bool {
+        self.policy_target_pointer(target).get_value::<u8>() == 1
+    }
+
+    fn target_allowed(&self, target: &AlkaneId) -> Result<bool> {
+        Ok(match self.policy_mode()? {
+            PolicyMode::Open => true,
+            PolicyMode::Allowlist => self.target_listed(target),
+            PolicyMode::Denylist => !self.target_listed(target),
+        })
+    }
+
+    fn check_target(&self, target: &AlkaneId) -> Result<()> {
+        if !self.target_allowed(target)? {
+            return Err(
+                ProxyError::TargetForbidden(format!("{}:{}", target.block, target.tx)).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Opcode handler setting the policy mode, `0` open, `1` allowlist, `2`
+    /// denylist. Requires the auth token.
+    fn set_target_policy(&self, mode: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.store_policy_mode(mode)?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler adding the target to the policy list when `listed` is
+    /// `1`, removing it when `listed` is `0`. Requires the auth token.
+    fn set_target_listed(&self, block: u128, tx: u128, listed: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        self.store_target_listed(&AlkaneId::new(block, tx), listed != 0)?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    /// Opcode handler returning a single byte, `1` if the policy allows
+    /// calling the target and `0` otherwise.
+    fn is_target_allowed(&self, block: u128, tx: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response
+            .data
+            .push(self.target_allowed(&AlkaneId::new(block, tx))? as u8);
+        Ok(response)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::payload::{FLAG_DEFLATE, FLAG_GZIP, NONCE_LEN, SIGNATURE_LEN};
+use crate::ProxyError;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
+
+/// Tag of the BIP340 style tagged hash signed payloads commit to. Keeps a
+/// signature over a cellpack from being valid for any other protocol.
+pub const SIGNATURE_DOMAIN: &[u8] = b"alkanes/witness-proxy/signed-payload";
+
+/// Assembles the x-only key registered at initialization from its two
+/// little endian halves.
+pub fn signer_key(key_0: u128, key_1: u128) -> Result<Vec<u8>> {
+    let mut key = key_0.to_le_bytes().to_vec();
+    key.extend(key_1.to_le_bytes());
+    XOnlyPublicKey::from_slice(&key).map_err(|_| {
+        ProxyError::InvalidInput("signer is not a valid x-only public key".to_string())
+    })?;
+    Ok(key)
+}
+
+/// The digest a signer signs: the tagged hash of the proxy id, the execution
+/// mode, the header flags without the compression bits, and the signed
+/// message. A signature is only valid for the deployment it was made for,
+/// and cannot be replayed under another mode or with the assertions flag
+/// toggled. Compression is left out so the body can be recompressed freely.
+pub fn signature_hash(proxy: &AlkaneId, mode: u8, flags: u8, message: &[u8]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(SIGNATURE_DOMAIN);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(&<AlkaneId as Into<Vec<u8>>>::into(*proxy));
+    engine.input(&[mode, flags & !(FLAG_DEFLATE | FLAG_GZIP)]);
+    engine.input(message);
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Splits a signed body into its signature and the signed message, which is
+/// the signer nonce followed by the cellpack bytes.
+pub fn split_signed(body: &[u8]) -> Result<(&[u8], &[u8])> {
+    if body.len() < SIGNATURE_LEN + NONCE_LEN {
+        return Err(ProxyError::Unauthorized(
+            "signed payload is shorter than a signature and nonce".to_string(),
+        )
+        .into());
+    }
+    Ok(body.split_at(SIGNATURE_LEN))
+}
+
+/// Splits a signed message into the little endian signer nonce and the
+/// cellpack bytes.
+pub fn split_nonce(message: &[u8]) -> Result<(u128, &[u8])> {
+    if message.len() < NONCE_LEN {
+        return Err(
+            ProxyError::Unauthorized("signed message is shorter than a nonce".to_string()).into(),
+        );
+    }
+    let (nonce, cellpack) = message.split_at(NONCE_LEN);
+    Ok((u128::from_le_bytes(nonce.try_into()?), cellpack))
+}
+
+pub fn verify(
+    signer: &[u8],
+    proxy: &AlkaneId,
+    mode: u8,
+    flags: u8,
+    signature: &[u8],
+    message: &[u8],
+) -> Result<()> {
+    let key = XOnlyPublicKey::from_slice(signer).map_err(|_| {
+        ProxyError::Unauthorized("registered signer is not a valid x-only public key".to_string())
+    })?;
+    let signature = schnorr::Signature::from_slice(signature)
+        .map_err(|_| ProxyError::Unauthorized("payload signature is malformed".to_string()))?;
+    let digest = Message::from_digest(signature_hash(proxy, mode, flags, message));
+    Secp256k1::verification_only()
+        .verify_schnorr(&signature, &digest, &key)
+        .map_err(|_| {
+            ProxyError::Unauthorized(
+                "payload signature does not match the registered signer".to_string(),
+            )
+        })?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::payload::{self, PayloadHeader};
+use crate::ProxyError;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::envelope::RawEnvelope;
+use anyhow::Result;
+use bitcoin::Transaction;
+
+/// Returns the first envelope of the transaction that carries the witness
+/// proxy header. Envelopes without it are skipped.
+pub fn extract_witness_payload(tx: &Transaction) -> Option<Vec<u8>> {
+    locate_witness_payload(tx).map(|(_, data)| data)
+}
+
+/// Like `extract_witness_payload`, but also returns the index of the input
+/// the envelope was found in.
+pub fn locate_witness_payload(tx: &Transaction) -> Option<(u32, Vec<u8>)> {
+    // Try every envelope of every input; Ordinals conventionally uses
+    // index 0, but looping covers edge‑cases.
+    RawEnvelope::from_transaction(tx)
+        .into_iter()
+        .map(|raw| (raw.input, envelope_payload(raw)))
+        .find(|(_, data)| PayloadHeader::is_tagged(data))
+}
+
+/// Returns the payload of the `envelope`-th envelope of input `input`, without
+/// looking at any other input.
+pub fn extract_witness_payload_at(tx: &Transaction, input: u32, envelope: u32) -> Option<Vec<u8>> {
+    RawEnvelope::from_transaction(tx)
+        .into_iter()
+        .filter(|raw| raw.input == input)
+        .nth(envelope as usize)
+        .map(envelope_payload)
+        .filter(|data| !data.is_empty())
+}
+
+/// Reads the single cellpack carried by the first tagged envelope, for
+/// contracts that do not check payload signatures or assertions. Signed
+/// payloads and payloads with assertions are rejected rather than run
+/// without those checks.
+pub fn read_witness_cellpack(tx: &Transaction) -> Result<Cellpack> {
+    let bytes = extract_witness_payload(tx).ok_or(ProxyError::NoPayload)?;
+    let (header, body) = PayloadHeader::parse(&bytes)?;
+    if header.is_signed() {
+        return Err(ProxyError::Unauthorized(
+            "signed payloads are only accepted by the witness proxy".to_string(),
+        )
+        .into());
+    }
+    if header.has_assertions() {
+        return Err(ProxyError::MalformedPayload(
+            "assertions are only checked by the witness proxy".to_string(),
+        )
+        .into());
+    }
+    payload::decode_single(payload::decode_values(header.decode_body(body)?)?)
+}
+
+// Same layout `find_witness_payload` reads: the body tag push is skipped and
+// the remaining pushes are concatenated.
+fn envelope_payload(raw: RawEnvelope) -> Vec<u8> {
+    raw.payload.into_iter().skip(1).flatten().collect()
+}
@@ -0,0 +1,57 @@
+use super::transaction::{protostone_script, unsigned_input};
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::absolute::LockTime;
+use bitcoin::transaction::Version;
+use bitcoin::{Amount, OutPoint, ScriptBuf, Transaction, TxOut};
+
+/// The inputs of a diesel proxy fallback call to `target`:
+/// `[block, tx, ...inputs]`.
+pub fn diesel_inputs(target: &Cellpack) -> Vec<u128> {
+    let mut inputs = vec![target.target.block, target.target.tx];
+    inputs.extend(target.inputs.iter());
+    inputs
+}
+
+/// The call the protostone makes to mint and call `target` through the
+/// diesel proxy `proxy`.
+pub fn diesel_call(proxy: AlkaneId, target: &Cellpack) -> Cellpack {
+    Cellpack {
+        target: proxy,
+        inputs: diesel_inputs(target),
+    }
+}
+
+/// An unsigned transaction spending `funding`, with `postage` sent to
+/// `recipient` at output `0`, the protostone calling the diesel proxy at
+/// output `1` and the optional change after it.
+pub fn build_diesel_call(
+    funding: &[OutPoint],
+    recipient: ScriptBuf,
+    postage: Amount,
+    change: Option<TxOut>,
+    proxy: AlkaneId,
+    target: &Cellpack,
+) -> Result<Transaction> {
+    let mut output = vec![
+        TxOut {
+            value: postage,
+            script_pubkey: recipient,
+        },
+        TxOut {
+            value: Amount::ZERO,
+            script_pubkey: protostone_script(&diesel_call(proxy, target))?,
+        },
+    ];
+    output.extend(change);
+    Ok(Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: funding
+            .iter()
+            .map(|outpoint| unsigned_input(*outpoint))
+            .collect(),
+        output,
+    })
+}
@@ -0,0 +1,164 @@
+use super::payload::{encode_payload, MAX_PUSH_LEN};
+use super::transaction::{fallback_call, protostone_script, WitnessProxyCall};
+use alkanes_support::cellpack::Cellpack;
+use anyhow::{anyhow, Result};
+use bitcoin::taproot::TAPROOT_CONTROL_BASE_SIZE;
+use bitcoin::{Amount, FeeRate, Script};
+
+/// Size of a `SIGHASH_DEFAULT` schnorr signature in a witness.
+const SIGNATURE_LEN: usize = 64;
+
+/// Size of a P2TR script pubkey, as used by the commit output.
+const P2TR_SCRIPT_LEN: usize = 34;
+
+/// Outpoint, empty script sig and sequence of a segwit input.
+const INPUT_LEN: usize = 36 + 1 + 4;
+
+/// The key push and `OP_CHECKSIG`, `OP_FALSE OP_IF`, the `"BIN"` tag push,
+/// the empty body tag push and `OP_ENDIF` around the payload pushes.
+const ENVELOPE_OVERHEAD: usize = 33 + 1 + 2 + 4 + 1 + 1;
+
+/// Sizes and fees of a commit and reveal pair, as built by
+/// `WitnessProxyCall::build` once both are signed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub commit_vsize: u64,
+    pub commit_fee: Amount,
+    pub reveal_vsize: u64,
+    pub reveal_fee: Amount,
+}
+
+impl FeeEstimate {
+    pub fn total_fee(&self) -> Amount {
+        self.commit_fee + self.reveal_fee
+    }
+
+    /// The commit output value that pays the reveal fee and `postage`.
+    pub fn commit_value(&self, postage: Amount) -> Amount {
+        self.reveal_fee + postage
+    }
+}
+
+/// What the estimate needs to know about the transactions besides the
+/// payload. Funding inputs are assumed to be P2TR key path spends.
+#[derive(Clone, Copy, Debug)]
+pub struct FeeParams<'a> {
+    /// The call the reveal makes to the proxy.
+    pub call: &'a Cellpack,
+    pub recipient: &'a Script,
+    pub funding_inputs: usize,
+    pub change: Option<&'a Script>,
+    pub fee_rate: FeeRate,
+}
+
+/// Estimates the commit and reveal of a payload of `payload_len` bytes.
+pub fn estimate_fees(payload_len: usize, params: &FeeParams) -> Result<FeeEstimate> {
+    let mut commit_outputs = vec![P2TR_SCRIPT_LEN];
+    commit_outputs.extend(params.change.map(|script| script.len()));
+    let commit_weight = tx_weight(
+        &vec![vec![SIGNATURE_LEN]; params.funding_inputs],
+        &commit_outputs,
+    );
+    let reveal_weight = tx_weight(
+        &[vec![
+            SIGNATURE_LEN,
+            envelope_script_len(payload_len),
+            TAPROOT_CONTROL_BASE_SIZE,
+        ]],
+        &[
+            params.recipient.len(),
+            protostone_script(params.call)?.len(),
+        ],
+    );
+    let commit_vsize = commit_weight.div_ceil(4) as u64;
+    let reveal_vsize = reveal_weight.div_ceil(4) as u64;
+    Ok(FeeEstimate {
+        commit_vsize,
+        commit_fee: fee(params.fee_rate, commit_vsize)?,
+        reveal_vsize,
+        reveal_fee: fee(params.fee_rate, reveal_vsize)?,
+    })
+}
+
+impl WitnessProxyCall {
+    /// Estimates the pair `build` produces, once the commit inputs and the
+    /// reveal are signed.
+    pub fn estimate_fees(&self, fee_rate: FeeRate) -> Result<FeeEstimate> {
+        estimate_fees(
+            encode_payload(&self.target).len(),
+            &FeeParams {
+                call: &fallback_call(self.proxy),
+                recipient: &self.recipient,
+                funding_inputs: self.funding.len(),
+                change: self
+                    .change
+                    .as_ref()
+                    .map(|change| change.script_pubkey.as_script()),
+                fee_rate,
+            },
+        )
+    }
+}
+
+/// Length of the reveal script for a payload of `payload_len` bytes, which
+/// is split into pushes of at most 520 bytes.
+pub fn envelope_script_len(payload_len: usize) -> usize {
+    let full = payload_len / MAX_PUSH_LEN;
+    let rest = payload_len % MAX_PUSH_LEN;
+    let mut len = ENVELOPE_OVERHEAD + full * push_len(MAX_PUSH_LEN);
+    if rest > 0 {
+        len += push_len(rest);
+    }
+    len
+}
+
+/// Size of a push of `len` bytes, including its opcode and length prefix.
+fn push_len(len: usize) -> usize {
+    match len {
+        0..=75 => 1 + len,
+        76..=255 => 2 + len,
+        256..=65535 => 3 + len,
+        _ => 5 + len,
+    }
+}
+
+fn varint_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Weight of a segwit transaction given the witness item sizes of each
+/// input and the script pubkey size of each output.
+fn tx_weight(witnesses: &[Vec<usize>], outputs: &[usize]) -> usize {
+    let base = 4
+        + varint_len(witnesses.len())
+        + witnesses.len() * INPUT_LEN
+        + varint_len(outputs.len())
+        + outputs
+            .iter()
+            .map(|len| 8 + varint_len(*len) + len)
+            .sum::<usize>()
+        + 4;
+    // Segwit marker and flag
+    let witness = 2 + witnesses
+        .iter()
+        .map(|items| {
+            varint_len(items.len())
+                + items
+                    .iter()
+                    .map(|len| varint_len(*len) + len)
+                    .sum::<usize>()
+        })
+        .sum::<usize>();
+    base * 4 + witness
+}
+
+fn fee(fee_rate: FeeRate, vsize: u64) -> Result<Amount> {
+    fee_rate
+        .fee_vb(vsize)
+        .ok_or_else(|| anyhow!("fee overflows at {} vbytes", vsize))
+}
@@ -0,0 +1,20 @@
+//! Client side helpers for calling the proxies: payload encoding, the
+//! commit and reveal transactions that carry a witness proxy payload, diesel
+//! proxy calls, and unsigned PSBTs of all of them.
+
+pub mod diesel;
+pub mod fee;
+pub mod payload;
+pub mod psbt;
+pub mod transaction;
+
+use alkanes_support::id::AlkaneId;
+
+/// The witness proxy deployment on signet.
+pub const SIGNET_WITNESS_PROXY: AlkaneId = AlkaneId { block: 4, tx: 1100 };
+
+/// The diesel proxy deployment on signet.
+pub const SIGNET_DIESEL_PROXY: AlkaneId = AlkaneId {
+    block: 4,
+    tx: 11001,
+};
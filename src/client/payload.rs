@@ -0,0 +1,102 @@
+use alkanes_proxy_support::payload::{PayloadHeader, FLAG_SIGNED, PAYLOAD_VERSION};
+use alkanes_proxy_support::signature::signature_hash;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use bitcoin::opcodes;
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::secp256k1::{Keypair, Message, Secp256k1, Signing, XOnlyPublicKey};
+use bitcoin::ScriptBuf;
+use protorune_support::utils::encode_varint_list;
+
+/// Protocol tag of the envelope, as read by `RawEnvelope`.
+pub const ENVELOPE_TAG: &[u8; 3] = b"BIN";
+
+/// Largest push tapscript allows; longer payloads are split into several.
+pub const MAX_PUSH_LEN: usize = 520;
+
+/// `[block, tx, ...inputs]`, the values a single cellpack payload carries.
+pub fn cellpack_values(cellpack: &Cellpack) -> Vec<u128> {
+    let mut values = vec![cellpack.target.block, cellpack.target.tx];
+    values.extend(cellpack.inputs.iter());
+    values
+}
+
+/// `[len, block, tx, ...inputs, len, ...]`, the values a batch payload
+/// carries.
+pub fn batch_values(cellpacks: &[Cellpack]) -> Vec<u128> {
+    cellpacks
+        .iter()
+        .flat_map(|cellpack| {
+            let values = cellpack_values(cellpack);
+            std::iter::once(values.len() as u128).chain(values)
+        })
+        .collect()
+}
+
+/// Encodes varint values behind a payload header with the given flags. The
+/// values are not compressed or signed here, so `flags` must only carry bits
+/// that describe the values themselves, such as `FLAG_ASSERTIONS`.
+pub fn encode_values(values: &[u128], flags: u8) -> Vec<u8> {
+    let mut payload = PayloadHeader {
+        version: PAYLOAD_VERSION,
+        flags,
+    }
+    .encode();
+    payload.extend(encode_varint_list(&values.to_vec()));
+    payload
+}
+
+/// Encodes varint values as a signed payload for `proxy`, to be run in
+/// `mode` (`0` single, `1` batch, `2` pipe, `3` try) with the signer's next
+/// `nonce`. `FLAG_SIGNED` is added to `flags`.
+pub fn encode_signed_values<C: Signing>(
+    secp: &Secp256k1<C>,
+    keypair: &Keypair,
+    proxy: &AlkaneId,
+    mode: u8,
+    nonce: u128,
+    values: &[u128],
+    flags: u8,
+) -> Vec<u8> {
+    let flags = flags | FLAG_SIGNED;
+    let mut message = nonce.to_le_bytes().to_vec();
+    message.extend(encode_varint_list(&values.to_vec()));
+    let digest = Message::from_digest(signature_hash(proxy, mode, flags, &message));
+    let mut payload = PayloadHeader {
+        version: PAYLOAD_VERSION,
+        flags,
+    }
+    .encode();
+    payload.extend(secp.sign_schnorr_no_aux_rand(&digest, keypair).serialize());
+    payload.extend(message);
+    payload
+}
+
+/// The payload `WitnessProxy::fallback` reads for a single call.
+pub fn encode_payload(cellpack: &Cellpack) -> Vec<u8> {
+    encode_values(&cellpack_values(cellpack), 0)
+}
+
+/// The payload `ExecuteBatch`, `ExecutePipe` and `ExecuteTry` read.
+pub fn encode_batch_payload(cellpacks: &[Cellpack]) -> Vec<u8> {
+    encode_values(&batch_values(cellpacks), 0)
+}
+
+/// The reveal tapscript: a `<key> OP_CHECKSIG` spend condition followed by
+/// the envelope `OP_FALSE OP_IF "BIN" <> <payload chunks> OP_ENDIF`.
+pub fn envelope_script(key: &XOnlyPublicKey, payload: &[u8]) -> Result<ScriptBuf> {
+    let mut builder = Builder::new()
+        .push_x_only_key(key)
+        .push_opcode(opcodes::all::OP_CHECKSIG)
+        .push_opcode(opcodes::OP_FALSE)
+        .push_opcode(opcodes::all::OP_IF)
+        .push_slice(ENVELOPE_TAG)
+        .push_slice(PushBytesBuf::new());
+    for chunk in payload.chunks(MAX_PUSH_LEN) {
+        let chunk = PushBytesBuf::try_from(chunk.to_vec())
+            .map_err(|_| anyhow!("payload chunk exceeds the push limit"))?;
+        builder = builder.push_slice(chunk);
+    }
+    Ok(builder.push_opcode(opcodes::all::OP_ENDIF).into_script())
+}
@@ -0,0 +1,126 @@
+use super::transaction::{CommitReveal, RevealScript};
+use anyhow::{anyhow, Result};
+use bitcoin::bip32::KeySource;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::taproot::{LeafVersion, TapTree, TaprootBuilder};
+use bitcoin::{Psbt, Transaction, TxOut, Witness};
+
+/// An output a transaction spends, as a PSBT input needs it.
+#[derive(Clone, Debug)]
+pub struct FundingUtxo {
+    pub utxo: TxOut,
+    /// Internal key of a P2TR output spent by key path, so taproot aware
+    /// signers know which key to tweak.
+    pub tap_internal_key: Option<XOnlyPublicKey>,
+    /// Master fingerprint and derivation path of `tap_internal_key`, so
+    /// signers holding many keys can tell which one to sign with.
+    pub key_origin: Option<KeySource>,
+}
+
+/// A PSBT for a transaction that spends wallet outputs, such as a commit or
+/// a diesel proxy call. `funding` lists the spent outputs in input order.
+pub fn funding_psbt(tx: Transaction, funding: &[FundingUtxo]) -> Result<Psbt> {
+    if tx.input.len() != funding.len() {
+        return Err(anyhow!(
+            "transaction has {} inputs but {} funding outputs were given",
+            tx.input.len(),
+            funding.len()
+        ));
+    }
+    let mut psbt = Psbt::from_unsigned_tx(tx)?;
+    for (input, funding) in psbt.inputs.iter_mut().zip(funding.iter()) {
+        input.witness_utxo = Some(funding.utxo.clone());
+        input.tap_internal_key = funding.tap_internal_key;
+        if let (Some(key), Some(origin)) = (funding.tap_internal_key, &funding.key_origin) {
+            input
+                .tap_key_origins
+                .insert(key, (Vec::new(), origin.clone()));
+        }
+    }
+    Ok(psbt)
+}
+
+/// A PSBT for the commit, describing the reveal script tree of output `0`
+/// so signers can check what they are committing to.
+pub fn commit_psbt(
+    commit: Transaction,
+    funding: &[FundingUtxo],
+    reveal: &RevealScript,
+) -> Result<Psbt> {
+    let mut psbt = funding_psbt(commit, funding)?;
+    let tree = TapTree::try_from(TaprootBuilder::new().add_leaf(0, reveal.script.clone())?)
+        .map_err(|_| anyhow!("reveal script tree is incomplete"))?;
+    psbt.outputs[0].tap_internal_key = Some(reveal.spend_info.internal_key());
+    psbt.outputs[0].tap_tree = Some(tree);
+    Ok(psbt)
+}
+
+/// A PSBT for the reveal, with the tapscript leaf and control block of the
+/// envelope so external signers can complete the script path spend.
+/// `key_origin` is the master fingerprint and derivation path of the reveal
+/// key, which signs for the leaf.
+pub fn reveal_psbt(
+    reveal_tx: Transaction,
+    commit_output: TxOut,
+    reveal: &RevealScript,
+    key_origin: Option<KeySource>,
+) -> Result<Psbt> {
+    let mut psbt = Psbt::from_unsigned_tx(reveal_tx)?;
+    let input = &mut psbt.inputs[0];
+    input.witness_utxo = Some(commit_output);
+    input.tap_internal_key = Some(reveal.spend_info.internal_key());
+    input.tap_merkle_root = reveal.spend_info.merkle_root();
+    input.tap_scripts.insert(
+        reveal.control_block()?,
+        (reveal.script.clone(), LeafVersion::TapScript),
+    );
+    if let Some(origin) = key_origin {
+        input.tap_key_origins.insert(
+            reveal.spend_info.internal_key(),
+            (vec![reveal.leaf_hash()], origin),
+        );
+    }
+    Ok(psbt)
+}
+
+/// Moves the script path signature of a signed reveal PSBT into the final
+/// witness `[signature, script, control block]` and extracts the reveal.
+pub fn finalize_reveal(mut psbt: Psbt) -> Result<Transaction> {
+    let input = &mut psbt.inputs[0];
+    let signature = input
+        .tap_script_sigs
+        .values()
+        .next()
+        .ok_or_else(|| anyhow!("reveal PSBT carries no script path signature"))?;
+    let (control_block, (script, _)) = input
+        .tap_scripts
+        .iter()
+        .next()
+        .ok_or_else(|| anyhow!("reveal PSBT carries no tapscript leaf"))?;
+    input.final_script_witness = Some(Witness::from_slice(&[
+        signature.to_vec(),
+        script.to_bytes(),
+        control_block.serialize(),
+    ]));
+    Ok(psbt.extract_tx_unchecked_fee_rate())
+}
+
+impl CommitReveal {
+    /// PSBTs for the commit and the reveal. `funding` lists the outputs the
+    /// commit spends, in input order, and `reveal_key_origin` is the origin
+    /// of the reveal key.
+    pub fn to_psbts(
+        &self,
+        funding: &[FundingUtxo],
+        reveal_key_origin: Option<KeySource>,
+    ) -> Result<(Psbt, Psbt)> {
+        let commit = commit_psbt(self.commit.clone(), funding, &self.reveal_script)?;
+        let reveal = reveal_psbt(
+            self.reveal.clone(),
+            self.commit.output[0].clone(),
+            &self.reveal_script,
+            reveal_key_origin,
+        )?;
+        Ok((commit, reveal))
+    }
+}
@@ -0,0 +1,234 @@
+use super::payload::{encode_payload, envelope_script};
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::{anyhow, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::{Keypair, Message, Secp256k1, Signing, Verification, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot::{
+    self, ControlBlock, LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo,
+};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Amount, OutPoint, ScriptBuf, Sequence, TapSighashType, Transaction, TxIn, TxOut, Witness,
+};
+use ordinals::Runestone;
+use protorune_support::protostone::{Protostone, Protostones};
+
+/// Protostone protocol tag of alkanes.
+pub const ALKANES_PROTOCOL_TAG: u128 = 1;
+
+/// The tapscript leaf a commit output locks the payload into, and the
+/// taproot tree it belongs to.
+#[derive(Clone, Debug)]
+pub struct RevealScript {
+    pub script: ScriptBuf,
+    pub spend_info: TaprootSpendInfo,
+}
+
+impl RevealScript {
+    /// Commits to `payload` behind a single `<key> OP_CHECKSIG` leaf. `key`
+    /// is both the internal key and the key that signs the reveal.
+    pub fn new<C: Verification>(
+        secp: &Secp256k1<C>,
+        key: XOnlyPublicKey,
+        payload: &[u8],
+    ) -> Result<Self> {
+        let script = envelope_script(&key, payload)?;
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())?
+            .finalize(secp, key)
+            .map_err(|_| anyhow!("failed to finalize the reveal taproot tree"))?;
+        Ok(RevealScript { script, spend_info })
+    }
+
+    /// The script pubkey of the commit output.
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_p2tr_tweaked(self.spend_info.output_key())
+    }
+
+    pub fn control_block(&self) -> Result<ControlBlock> {
+        self.spend_info
+            .control_block(&(self.script.clone(), LeafVersion::TapScript))
+            .ok_or_else(|| anyhow!("reveal script is not a leaf of its taproot tree"))
+    }
+
+    pub fn leaf_hash(&self) -> TapLeafHash {
+        TapLeafHash::from_script(&self.script, LeafVersion::TapScript)
+    }
+}
+
+/// The runestone output that calls `call`, whose target is usually the
+/// proxy. Alkanes the call returns go to output `0` of the reveal.
+pub fn protostone_script(call: &Cellpack) -> Result<ScriptBuf> {
+    let protostone = Protostone {
+        burn: None,
+        message: call.encipher(),
+        edicts: Vec::new(),
+        refund: Some(0),
+        pointer: Some(0),
+        from: None,
+        protocol_tag: ALKANES_PROTOCOL_TAG,
+    };
+    Ok(Runestone {
+        etching: None,
+        pointer: Some(0),
+        edicts: Vec::new(),
+        mint: None,
+        protocol: Some(vec![protostone].encipher()?),
+    }
+    .encipher())
+}
+
+/// An unsigned transaction spending `inputs` into the commit output, plus
+/// an optional change output.
+pub fn build_commit(
+    inputs: &[OutPoint],
+    reveal: &RevealScript,
+    commit_value: Amount,
+    change: Option<TxOut>,
+) -> Transaction {
+    let mut output = vec![TxOut {
+        value: commit_value,
+        script_pubkey: reveal.script_pubkey(),
+    }];
+    output.extend(change);
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs
+            .iter()
+            .map(|outpoint| unsigned_input(*outpoint))
+            .collect(),
+        output,
+    }
+}
+
+/// An unsigned transaction spending the commit output, with `postage` sent
+/// to `recipient` at output `0` and the protostone calling `call` at output
+/// `1`. Sign it with `sign_reveal`.
+pub fn build_reveal(
+    commit: OutPoint,
+    recipient: ScriptBuf,
+    postage: Amount,
+    call: &Cellpack,
+) -> Result<Transaction> {
+    Ok(Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![unsigned_input(commit)],
+        output: vec![
+            TxOut {
+                value: postage,
+                script_pubkey: recipient,
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: protostone_script(call)?,
+            },
+        ],
+    })
+}
+
+/// Signs the script path spend of the commit output, leaving the witness as
+/// `[signature, script, control block]`, which is where `RawEnvelope` reads
+/// the envelope from.
+pub fn sign_reveal<C: Signing>(
+    secp: &Secp256k1<C>,
+    reveal_tx: &mut Transaction,
+    commit_output: &TxOut,
+    reveal: &RevealScript,
+    keypair: &Keypair,
+) -> Result<()> {
+    let sighash = SighashCache::new(&*reveal_tx).taproot_script_spend_signature_hash(
+        0,
+        &Prevouts::All(std::slice::from_ref(commit_output)),
+        reveal.leaf_hash(),
+        TapSighashType::Default,
+    )?;
+    let signature = taproot::Signature {
+        signature: secp
+            .sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), keypair),
+        sighash_type: TapSighashType::Default,
+    };
+    reveal_tx.input[0].witness = Witness::from_slice(&[
+        signature.to_vec(),
+        reveal.script.to_bytes(),
+        reveal.control_block()?.serialize(),
+    ]);
+    Ok(())
+}
+
+/// The call the reveal makes to run the proxy fallback, which executes the
+/// single cellpack in the payload. Any opcode the proxy does not declare
+/// reaches the fallback; `0` is used.
+pub fn fallback_call(proxy: AlkaneId) -> Cellpack {
+    Cellpack {
+        target: proxy,
+        inputs: vec![0],
+    }
+}
+
+/// Everything needed to call `target` through a witness proxy with a
+/// commit and reveal pair.
+#[derive(Clone, Debug)]
+pub struct WitnessProxyCall {
+    /// The proxy the reveal calls, for example `SIGNET_WITNESS_PROXY`.
+    pub proxy: AlkaneId,
+    /// The call the proxy makes.
+    pub target: Cellpack,
+    /// Key of the reveal script, which signs the reveal.
+    pub key: XOnlyPublicKey,
+    /// Outputs the commit spends.
+    pub funding: Vec<OutPoint>,
+    /// Value of the commit output, which pays the reveal fee and postage.
+    pub commit_value: Amount,
+    pub change: Option<TxOut>,
+    /// Receives output `0` of the reveal and the alkanes the call returns.
+    pub recipient: ScriptBuf,
+    pub postage: Amount,
+}
+
+/// An unsigned commit and the reveal that spends its output `0`.
+#[derive(Clone, Debug)]
+pub struct CommitReveal {
+    pub reveal_script: RevealScript,
+    pub commit: Transaction,
+    pub reveal: Transaction,
+}
+
+impl WitnessProxyCall {
+    pub fn build<C: Verification>(&self, secp: &Secp256k1<C>) -> Result<CommitReveal> {
+        let reveal_script = RevealScript::new(secp, self.key, &encode_payload(&self.target))?;
+        let commit = build_commit(
+            &self.funding,
+            &reveal_script,
+            self.commit_value,
+            self.change.clone(),
+        );
+        let reveal = build_reveal(
+            OutPoint {
+                txid: commit.compute_txid(),
+                vout: 0,
+            },
+            self.recipient.clone(),
+            self.postage,
+            &fallback_call(self.proxy),
+        )?;
+        Ok(CommitReveal {
+            reveal_script,
+            commit,
+            reveal,
+        })
+    }
+}
+
+pub(crate) fn unsigned_input(previous_output: OutPoint) -> TxIn {
+    TxIn {
+        previous_output,
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+    }
+}
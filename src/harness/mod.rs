@@ -0,0 +1,133 @@
+//! Deploys the proxy fixtures into a local indexer state and reads the
+//! results back. Shared by the tests and the offline simulator.
+
+use alkanes::message::AlkaneMessageContext;
+use alkanes::precompiled::alkanes_std_auth_token_build;
+use alkanes::tests::helpers as alkane_helpers;
+use alkanes::view;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::constants::AUTH_TOKEN_FACTORY_ID;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::proto::alkanes::AlkanesTrace;
+use alkanes_support::trace::{Trace, TraceEvent};
+use anyhow::{anyhow, Result};
+use bitcoin::{Block, OutPoint, Transaction};
+use metashrew_support::index_pointer::KeyValuePointer;
+use metashrew_support::utils::consensus_encode;
+use protobuf::Message;
+use protorune::balance_sheet::load_sheet;
+use protorune::message::MessageContext;
+use protorune::tables::RuneTable;
+use protorune_support::balance_sheet::{BalanceSheetOperations, ProtoruneRuneId};
+
+#[path = "../tests/std/diesel_proxy_build.rs"]
+mod diesel_proxy_build;
+#[path = "../tests/std/witness_proxy_build.rs"]
+mod witness_proxy_build;
+
+/// The alkane the test fixture is deployed at.
+pub const TEST_ALKANE: AlkaneId = AlkaneId { block: 2, tx: 1 };
+
+/// A contract deployed before the transactions under test run.
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    pub binary: Vec<u8>,
+    /// The deployment call, which also initializes the contract.
+    pub cellpack: Cellpack,
+}
+
+/// The auth token factory the proxies mint their auth tokens from.
+pub fn auth_token_factory() -> Fixture {
+    Fixture {
+        binary: alkanes_std_auth_token_build::get_bytes(),
+        cellpack: Cellpack {
+            target: AlkaneId {
+                block: 3,
+                tx: AUTH_TOKEN_FACTORY_ID,
+            },
+            inputs: vec![100],
+        },
+    }
+}
+
+/// The test alkane, deployed at `TEST_ALKANE`. Opcode `22` mints the given
+/// amount of it.
+pub fn test_alkane() -> Fixture {
+    Fixture {
+        binary: include_bytes!("../tests/precompiled/alkanes_std_test.wasm").to_vec(),
+        cellpack: Cellpack {
+            target: AlkaneId { block: 1, tx: 0 },
+            inputs: vec![50],
+        },
+    }
+}
+
+/// The witness proxy, deployed at `4:tx` with the given signer key halves.
+pub fn witness_proxy(tx: u128, signer: [u128; 2]) -> Fixture {
+    Fixture {
+        binary: witness_proxy_build::get_bytes(),
+        cellpack: Cellpack {
+            target: AlkaneId { block: 3, tx },
+            inputs: vec![69690, signer[0], signer[1]],
+        },
+    }
+}
+
+/// The diesel proxy, deployed at `4:tx` with the given
+/// `[policy, block, tx, opcode, ...]` mint configuration.
+pub fn diesel_proxy(tx: u128, config: &[u128]) -> Fixture {
+    let mut inputs = vec![69690420];
+    inputs.extend(config);
+    Fixture {
+        binary: diesel_proxy_build::get_bytes(),
+        cellpack: Cellpack {
+            target: AlkaneId { block: 3, tx },
+            inputs,
+        },
+    }
+}
+
+/// A block deploying `fixtures` in order, one transaction each. Transactions
+/// under test are pushed after them.
+pub fn deploy(fixtures: &[Fixture]) -> Block {
+    alkane_helpers::init_with_multiple_cellpacks_with_tx(
+        fixtures.iter().map(|fixture| fixture.binary.clone()).collect(),
+        fixtures.iter().map(|fixture| fixture.cellpack.clone()).collect(),
+    )
+}
+
+/// The trace of the protostone traced at `outpoint`.
+pub fn trace_events(outpoint: &OutPoint) -> Result<Vec<TraceEvent>> {
+    let trace: Trace = AlkanesTrace::parse_from_bytes(&view::trace(outpoint)?)?.into();
+    let events = trace
+        .0
+        .lock()
+        .map_err(|_| anyhow!("trace lock is poisoned"))?
+        .clone();
+    Ok(events)
+}
+
+/// The alkanes held by `outpoint`, sorted by id.
+pub fn balances(outpoint: &OutPoint) -> Result<Vec<(ProtoruneRuneId, u128)>> {
+    let sheet = load_sheet(
+        &RuneTable::for_protocol(AlkaneMessageContext::protocol_tag())
+            .OUTPOINT_TO_RUNES
+            .select(&consensus_encode(outpoint)?),
+    );
+    let mut balances = sheet
+        .balances()
+        .iter()
+        .map(|(id, amount)| (*id, *amount))
+        .collect::<Vec<_>>();
+    balances.sort_by_key(|(id, _)| (id.block, id.tx));
+    Ok(balances)
+}
+
+/// The virtual outpoint the `index`-th protostone of `tx` is traced at:
+/// protostones follow the real outputs and the OP_RETURN shadow output.
+pub fn protostone_outpoint(tx: &Transaction, index: usize) -> OutPoint {
+    OutPoint {
+        txid: tx.compute_txid(),
+        vout: (tx.output.len() + 1 + index) as u32,
+    }
+}
@@ -1,2 +1,7 @@
+pub mod client;
+#[cfg(any(test, feature = "simulator"))]
+pub mod harness;
+#[cfg(feature = "simulator")]
+pub mod simulator;
 #[cfg(test)]
 pub mod tests;
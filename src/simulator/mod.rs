@@ -0,0 +1,107 @@
+//! Offline simulator that runs proxy calls against a local indexer state,
+//! to predict the outcome of a transaction without a live node.
+
+use crate::client::{SIGNET_DIESEL_PROXY, SIGNET_WITNESS_PROXY};
+use crate::harness::{
+    auth_token_factory, balances, deploy, diesel_proxy, protostone_outpoint, test_alkane,
+    trace_events, witness_proxy, Fixture,
+};
+use alkanes::indexer::index_block;
+use alkanes::tests::helpers::clear;
+use alkanes_support::trace::TraceEvent;
+use anyhow::Result;
+use bitcoin::{OutPoint, Transaction};
+use ordinals::{Artifact, Runestone};
+use protorune::test_helpers::create_block_with_coinbase_tx;
+use protorune_support::balance_sheet::ProtoruneRuneId;
+use protorune_support::protostone::Protostone;
+use std::fmt;
+
+/// The contracts the proxy tests run against: the auth token factory, the
+/// test alkane at `2:1`, and this tree's builds of both proxies at their
+/// signet addresses. DIESEL at `2:0` comes with the indexer genesis.
+///
+/// The contracts live on signet run older code, which reads no payload
+/// header and has no mint configuration, so a call that works here may not
+/// work there, and a signet reveal without the header fails here with code
+/// `1`. No balances are loaded either: alkanes the simulated transaction
+/// spends are missing unless an earlier simulated transaction created them.
+pub fn default_fixtures() -> Vec<Fixture> {
+    vec![
+        auth_token_factory(),
+        test_alkane(),
+        witness_proxy(SIGNET_WITNESS_PROXY.tx, [0, 0]),
+        diesel_proxy(SIGNET_DIESEL_PROXY.tx, &[0, 2, 0, 77]),
+    ]
+}
+
+/// What indexing a transaction produced.
+#[derive(Clone, Debug, Default)]
+pub struct Simulation {
+    /// The trace of every protostone, keyed by its virtual outpoint.
+    pub traces: Vec<(OutPoint, Vec<TraceEvent>)>,
+    /// The alkanes held by every output afterwards, sorted by id.
+    pub sheets: Vec<(OutPoint, Vec<(ProtoruneRuneId, u128)>)>,
+}
+
+/// A local indexer state with fixtures deployed, which indexes each
+/// simulated transaction in a block of its own.
+pub struct Simulator {
+    height: u32,
+}
+
+impl Simulator {
+    /// Clears the local state and indexes `fixtures` at `height`.
+    pub fn new(height: u32, fixtures: &[Fixture]) -> Result<Self> {
+        clear();
+        let block = deploy(fixtures);
+        index_block(&block, height)?;
+        Ok(Simulator { height })
+    }
+
+    /// Indexes `tx` in the next block and reads back its traces and the
+    /// balance sheets of its outputs.
+    pub fn simulate(&mut self, tx: &Transaction) -> Result<Simulation> {
+        self.height += 1;
+        let mut block = create_block_with_coinbase_tx(self.height);
+        block.txdata.push(tx.clone());
+        index_block(&block, self.height)?;
+
+        let txid = tx.compute_txid();
+        let protostones = match Runestone::decipher(tx) {
+            Some(Artifact::Runestone(runestone)) => Protostone::from_runestone(&runestone)?.len(),
+            _ => 0,
+        };
+        let traces = (0..protostones)
+            .map(|i| {
+                let outpoint = protostone_outpoint(tx, i);
+                Ok((outpoint, trace_events(&outpoint)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let sheets = (0..tx.output.len() as u32)
+            .map(|vout| {
+                let outpoint = OutPoint { txid, vout };
+                Ok((outpoint, balances(&outpoint)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Simulation { traces, sheets })
+    }
+}
+
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (outpoint, events) in self.traces.iter() {
+            writeln!(f, "trace {}:", outpoint)?;
+            for event in events.iter() {
+                writeln!(f, "  {:?}", event)?;
+            }
+        }
+        for (outpoint, balances) in self.sheets.iter() {
+            writeln!(f, "sheet {}:", outpoint)?;
+            for (id, amount) in balances.iter() {
+                writeln!(f, "  {}:{} = {}", id.block, id.tx, amount)?;
+            }
+        }
+        Ok(())
+    }
+}
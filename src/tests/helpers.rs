@@ -0,0 +1,277 @@
+use crate::client::payload::envelope_script;
+use crate::client::transaction::ALKANES_PROTOCOL_TAG;
+use crate::harness::{self, deploy, protostone_outpoint, Fixture, TEST_ALKANE};
+use alkanes::indexer::index_block;
+use alkanes::tests::helpers::clear;
+use alkanes_proxy_support::ProxyError;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use alkanes_support::trace::TraceEvent;
+use anyhow::{anyhow, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey, XOnlyPublicKey};
+use bitcoin::transaction::Version;
+use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use ordinals::Runestone;
+use protorune::test_helpers::{create_block_with_coinbase_tx, get_address, ADDRESS1};
+use protorune_support::balance_sheet::ProtoruneRuneId;
+use protorune_support::protostone::{Protostone, ProtostoneEdict, Protostones};
+
+pub const BLOCK_HEIGHT: u32 = 880_000;
+
+/// Where a proxy deployed with `[3, 1]` lives.
+pub const PROXY: AlkaneId = AlkaneId { block: 4, tx: 1 };
+
+/// The auth token a proxy mints on initialize, when it is deployed after
+/// `harness::test_alkane`.
+pub const AUTH_TOKEN: AlkaneId = AlkaneId { block: 2, tx: 2 };
+
+pub const DIESEL: AlkaneId = AlkaneId { block: 2, tx: 0 };
+
+/// A call of the proxy at `PROXY` with `inputs`.
+pub fn proxy_call(inputs: Vec<u128>) -> Cellpack {
+    Cellpack {
+        target: PROXY,
+        inputs,
+    }
+}
+
+/// A call of the test alkane minting `amount` of it.
+pub fn mint(amount: u128) -> Cellpack {
+    Cellpack {
+        target: TEST_ALKANE,
+        inputs: vec![22, amount],
+    }
+}
+
+/// A local chain with fixtures deployed. Every call is indexed in a block of
+/// its own and spends output `0` of the previous one, so alkanes returned to
+/// the caller, such as the auth token, can be sent into the next call.
+pub struct Chain {
+    height: u32,
+    tip: OutPoint,
+}
+
+impl Chain {
+    pub fn new(fixtures: &[Fixture]) -> Result<Self> {
+        clear();
+        let block = deploy(fixtures);
+        index_block(&block, BLOCK_HEIGHT)?;
+        let last = block
+            .txdata
+            .last()
+            .ok_or_else(|| anyhow!("deploy block is empty"))?;
+        Ok(Chain {
+            height: BLOCK_HEIGHT,
+            tip: OutPoint {
+                txid: last.compute_txid(),
+                vout: 0,
+            },
+        })
+    }
+
+    /// The output the next call spends.
+    pub fn tip(&self) -> OutPoint {
+        self.tip
+    }
+
+    /// Makes `call` with `witness` on the spent input, moving `transfers`
+    /// from the tip into it.
+    pub fn call(
+        &mut self,
+        witness: Witness,
+        call: &Cellpack,
+        transfers: &[(AlkaneId, u128)],
+    ) -> Result<Transaction> {
+        let tx = call_transaction(self.tip, witness, call, transfers)?;
+        self.height += 1;
+        let mut block = create_block_with_coinbase_tx(self.height);
+        block.txdata.push(tx.clone());
+        index_block(&block, self.height)?;
+        self.tip = OutPoint {
+            txid: tx.compute_txid(),
+            vout: 0,
+        };
+        Ok(tx)
+    }
+
+    /// Calls the proxy with `opcode` and the auth token attached.
+    pub fn admin(&mut self, opcode: u128, args: &[u128]) -> Result<Transaction> {
+        self.admin_with(AUTH_TOKEN, opcode, args)
+    }
+
+    /// Calls the proxy with `opcode` and one unit of `token` attached.
+    pub fn admin_with(
+        &mut self,
+        token: AlkaneId,
+        opcode: u128,
+        args: &[u128],
+    ) -> Result<Transaction> {
+        let mut inputs = vec![opcode];
+        inputs.extend(args);
+        self.call(Witness::new(), &proxy_call(inputs), &[(token, 1)])
+    }
+
+    /// How much of `id` the tip holds.
+    pub fn balance(&self, id: AlkaneId) -> Result<u128> {
+        balance(&self.tip, id)
+    }
+}
+
+/// Spends `previous_output` into `[P2TR, OP_RETURN]`. The protostone sends
+/// `transfers` into `call` and everything else, including what the call
+/// returns or refunds, to output `0`.
+pub fn call_transaction(
+    previous_output: OutPoint,
+    witness: Witness,
+    call: &Cellpack,
+    transfers: &[(AlkaneId, u128)],
+) -> Result<Transaction> {
+    // Protostones are numbered after the two outputs and the OP_RETURN
+    // shadow output
+    let call_vout = 3;
+    let protostone = Protostone {
+        burn: None,
+        message: call.encipher(),
+        edicts: transfers
+            .iter()
+            .map(|(id, amount)| ProtostoneEdict {
+                id: ProtoruneRuneId {
+                    block: id.block,
+                    tx: id.tx,
+                },
+                amount: *amount,
+                output: call_vout,
+            })
+            .collect(),
+        refund: Some(0),
+        pointer: Some(0),
+        from: None,
+        protocol_tag: ALKANES_PROTOCOL_TAG,
+    };
+    let runestone = Runestone {
+        etching: None,
+        pointer: Some(0),
+        edicts: Vec::new(),
+        mint: None,
+        protocol: Some(vec![protostone].encipher()?),
+    };
+    Ok(Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness,
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(546),
+                script_pubkey: get_address(ADDRESS1().as_str()).script_pubkey(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: runestone.encipher(),
+            },
+        ],
+    })
+}
+
+/// The key the test envelopes are committed to.
+pub fn envelope_key() -> XOnlyPublicKey {
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[7; 32]).expect("valid secret key");
+    Keypair::from_secret_key(&secp, &secret)
+        .x_only_public_key()
+        .0
+}
+
+/// A script path witness `[script, control block]` carrying `payload` in
+/// the reveal envelope the client builds. The control block is left empty,
+/// the indexer does not check it.
+pub fn envelope_witness(payload: &[u8]) -> Witness {
+    let script = envelope_script(&envelope_key(), payload).expect("payload fits the envelope");
+    let mut witness = Witness::new();
+    witness.push(script.as_bytes());
+    witness.push(Vec::<u8>::new());
+    witness
+}
+
+/// A witness with one envelope per payload, in order, each in a leaf of its
+/// own script.
+pub fn envelopes_witness(payloads: &[&[u8]]) -> Witness {
+    let script = payloads
+        .iter()
+        .flat_map(|payload| {
+            envelope_script(&envelope_key(), payload)
+                .expect("payload fits the envelope")
+                .into_bytes()
+        })
+        .collect::<Vec<u8>>();
+    let mut witness = Witness::new();
+    witness.push(script);
+    witness.push(Vec::<u8>::new());
+    witness
+}
+
+/// How much of `id` `outpoint` holds.
+pub fn balance(outpoint: &OutPoint, id: AlkaneId) -> Result<u128> {
+    Ok(harness::balances(outpoint)?
+        .into_iter()
+        .find(|(rune, _)| rune.block == id.block && rune.tx == id.tx)
+        .map(|(_, amount)| amount)
+        .unwrap_or(0))
+}
+
+/// The event ending the outermost call of the first protostone of `tx`.
+fn last_event(tx: &Transaction) -> Result<TraceEvent> {
+    harness::trace_events(&protostone_outpoint(tx, 0))?
+        .pop()
+        .ok_or_else(|| anyhow!("transaction was not traced"))
+}
+
+/// The data the call of `tx` returned. Fails if it reverted.
+pub fn return_data(tx: &Transaction) -> Result<Vec<u8>> {
+    match last_event(tx)? {
+        TraceEvent::ReturnContext(response) => Ok(response.inner.data),
+        event => Err(anyhow!("call did not return: {:?}", event)),
+    }
+}
+
+/// The `PROXY_ERROR` code the call of `tx` reverted with. Fails if it
+/// returned.
+pub fn revert_code(tx: &Transaction) -> Result<u32> {
+    match last_event(tx)? {
+        TraceEvent::RevertContext(response) => ProxyError::code_from_revert(&response.inner.data)
+            .ok_or_else(|| anyhow!("revert carries no proxy error code")),
+        event => Err(anyhow!("call did not revert: {:?}", event)),
+    }
+}
+
+/// The alkanes every call into `target` made by `tx` received, one entry
+/// per call.
+pub fn incoming_to(tx: &Transaction, target: AlkaneId) -> Result<Vec<Vec<(AlkaneId, u128)>>> {
+    Ok(harness::trace_events(&protostone_outpoint(tx, 0))?
+        .into_iter()
+        .filter_map(|event| match event {
+            TraceEvent::EnterCall(context) if context.target == target => Some(
+                context
+                    .inner
+                    .incoming_alkanes
+                    .0
+                    .iter()
+                    .map(|transfer| (transfer.id, transfer.value))
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Reads the `u128` little endian values of a response.
+pub fn u128_values(data: &[u8]) -> Vec<u128> {
+    data.chunks_exact(16)
+        .map(|chunk| u128::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
@@ -1,2 +1,10 @@
+pub mod helpers;
 pub mod std;
+pub mod test_admin;
+pub mod test_client;
 pub mod test_diesel_proxy;
+pub mod test_payload;
+pub mod test_signed_payload;
+#[cfg(feature = "simulator")]
+pub mod test_simulator;
+pub mod test_witness_proxy;
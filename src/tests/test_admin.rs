@@ -0,0 +1,283 @@
+use crate::client::payload::encode_payload;
+use crate::harness::{auth_token_factory, diesel_proxy, test_alkane, witness_proxy, TEST_ALKANE};
+use crate::tests::helpers::{
+    envelope_witness, mint, proxy_call, return_data, revert_code, u128_values, Chain, AUTH_TOKEN,
+};
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::{Transaction, Witness};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// The auth token `MintAuthToken` mints, the next alkane after `AUTH_TOKEN`.
+const ROTATED_AUTH_TOKEN: AlkaneId = AlkaneId { block: 2, tx: 3 };
+
+#[derive(Clone, Copy, Debug)]
+enum Proxy {
+    Witness,
+    Diesel,
+}
+
+/// The admin opcodes, which both proxies number in this order from their
+/// own base opcode.
+#[derive(Clone, Copy)]
+enum Admin {
+    SetTargetPolicy = 0,
+    SetTargetListed = 1,
+    IsTargetAllowed = 2,
+    Pause = 3,
+    Unpause = 4,
+    IsPaused = 5,
+    MintAuthToken = 6,
+    TransferOwnership = 7,
+    AcceptOwnership = 8,
+    RenounceOwnership = 9,
+    GetAdmin = 10,
+}
+
+const PROXIES: [Proxy; 2] = [Proxy::Witness, Proxy::Diesel];
+
+impl Proxy {
+    fn setup(self) -> Result<Chain> {
+        let proxy = match self {
+            Proxy::Witness => witness_proxy(1, [0, 0]),
+            Proxy::Diesel => diesel_proxy(1, &[0, 2, 0, 77]),
+        };
+        Chain::new(&[auth_token_factory(), test_alkane(), proxy])
+    }
+
+    fn opcode(self, admin: Admin) -> u128 {
+        let base = match self {
+            Proxy::Witness => 69696,
+            Proxy::Diesel => 69690428,
+        };
+        base + admin as u128
+    }
+
+    /// Calls the test alkane through the proxy to mint `amount` of it.
+    fn mint_through(self, chain: &mut Chain, amount: u128) -> Result<Transaction> {
+        match self {
+            Proxy::Witness => chain.call(
+                envelope_witness(&encode_payload(&mint(amount))),
+                &proxy_call(vec![0]),
+                &[],
+            ),
+            Proxy::Diesel => chain.call(
+                Witness::new(),
+                &proxy_call(vec![TEST_ALKANE.block, TEST_ALKANE.tx, 22, amount]),
+                &[],
+            ),
+        }
+    }
+}
+
+/// Mints `amount` of the test alkane to the tip without the proxy.
+fn mint_directly(chain: &mut Chain, amount: u128) -> Result<()> {
+    chain.call(Witness::new(), &mint(amount), &[])?;
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_admin_requires_auth_token() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![proxy.opcode(Admin::SetTargetPolicy), 1]),
+            &[],
+        )?;
+        assert_eq!(revert_code(&tx)?, 9, "{:?}", proxy);
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![
+                proxy.opcode(Admin::SetTargetListed),
+                TEST_ALKANE.block,
+                TEST_ALKANE.tx,
+                1,
+            ]),
+            &[],
+        )?;
+        assert_eq!(revert_code(&tx)?, 9, "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_allowlist_refuses_unlisted_target() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        chain.admin(proxy.opcode(Admin::SetTargetPolicy), &[1])?;
+
+        let tx = proxy.mint_through(&mut chain, 1000)?;
+        assert_eq!(revert_code(&tx)?, 8, "{:?}", proxy);
+
+        chain.admin(
+            proxy.opcode(Admin::SetTargetListed),
+            &[TEST_ALKANE.block, TEST_ALKANE.tx, 1],
+        )?;
+        proxy.mint_through(&mut chain, 1000)?;
+        assert_eq!(chain.balance(TEST_ALKANE)?, 1000, "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_denylist_refuses_listed_target() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        chain.admin(proxy.opcode(Admin::SetTargetPolicy), &[2])?;
+        chain.admin(
+            proxy.opcode(Admin::SetTargetListed),
+            &[TEST_ALKANE.block, TEST_ALKANE.tx, 1],
+        )?;
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![
+                proxy.opcode(Admin::IsTargetAllowed),
+                TEST_ALKANE.block,
+                TEST_ALKANE.tx,
+            ]),
+            &[],
+        )?;
+        assert_eq!(return_data(&tx)?, vec![0], "{:?}", proxy);
+
+        let tx = proxy.mint_through(&mut chain, 1000)?;
+        assert_eq!(revert_code(&tx)?, 8, "{:?}", proxy);
+        assert_eq!(chain.balance(TEST_ALKANE)?, 0, "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_paused_fallback_refunds() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        mint_directly(&mut chain, 500)?;
+        chain.admin(proxy.opcode(Admin::Pause), &[])?;
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![proxy.opcode(Admin::IsPaused)]),
+            &[],
+        )?;
+        assert_eq!(return_data(&tx)?, vec![1], "{:?}", proxy);
+
+        // The fallback reads nothing while paused, not even a target, and
+        // hands the incoming alkanes back
+        let tx = chain.call(Witness::new(), &proxy_call(vec![0]), &[(TEST_ALKANE, 500)])?;
+        assert!(return_data(&tx).is_ok(), "{:?}", proxy);
+        assert_eq!(chain.balance(TEST_ALKANE)?, 500, "{:?}", proxy);
+
+        chain.admin(proxy.opcode(Admin::Unpause), &[])?;
+        proxy.mint_through(&mut chain, 1000)?;
+        assert_eq!(chain.balance(TEST_ALKANE)?, 1500, "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_mint_auth_token_retires_old_token() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        chain.admin(proxy.opcode(Admin::MintAuthToken), &[1])?;
+        assert_eq!(chain.balance(ROTATED_AUTH_TOKEN)?, 1, "{:?}", proxy);
+        assert_eq!(chain.balance(AUTH_TOKEN)?, 1, "{:?}", proxy);
+
+        let tx = chain.admin(proxy.opcode(Admin::SetTargetPolicy), &[1])?;
+        assert_eq!(revert_code(&tx)?, 9, "{:?}", proxy);
+
+        let tx = chain.admin_with(
+            ROTATED_AUTH_TOKEN,
+            proxy.opcode(Admin::SetTargetPolicy),
+            &[1],
+        )?;
+        assert!(return_data(&tx).is_ok(), "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_ownership_handoff() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        mint_directly(&mut chain, 1)?;
+        chain.admin(
+            proxy.opcode(Admin::TransferOwnership),
+            &[TEST_ALKANE.block, TEST_ALKANE.tx],
+        )?;
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![proxy.opcode(Admin::GetAdmin)]),
+            &[],
+        )?;
+        assert_eq!(
+            u128_values(&return_data(&tx)?),
+            vec![2, 2, TEST_ALKANE.block, TEST_ALKANE.tx, 0],
+            "{:?}",
+            proxy
+        );
+
+        chain.admin_with(TEST_ALKANE, proxy.opcode(Admin::AcceptOwnership), &[])?;
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![proxy.opcode(Admin::GetAdmin)]),
+            &[],
+        )?;
+        assert_eq!(
+            u128_values(&return_data(&tx)?),
+            vec![TEST_ALKANE.block, TEST_ALKANE.tx, 0, 0, 0],
+            "{:?}",
+            proxy
+        );
+
+        let tx = chain.admin(proxy.opcode(Admin::Pause), &[])?;
+        assert_eq!(revert_code(&tx)?, 9, "{:?}", proxy);
+        let tx = chain.admin_with(TEST_ALKANE, proxy.opcode(Admin::Pause), &[])?;
+        assert!(return_data(&tx).is_ok(), "{:?}", proxy);
+    }
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_renounce_ownership_blocks_admin() -> Result<()> {
+    for proxy in PROXIES {
+        let mut chain = proxy.setup()?;
+        chain.admin(proxy.opcode(Admin::RenounceOwnership), &[])?;
+
+        for (admin, args) in [
+            (Admin::SetTargetPolicy, vec![1]),
+            (
+                Admin::SetTargetListed,
+                vec![TEST_ALKANE.block, TEST_ALKANE.tx, 1],
+            ),
+            (Admin::Pause, vec![]),
+            (Admin::Unpause, vec![]),
+            (Admin::MintAuthToken, vec![1]),
+            (
+                Admin::TransferOwnership,
+                vec![TEST_ALKANE.block, TEST_ALKANE.tx],
+            ),
+            (Admin::AcceptOwnership, vec![]),
+            (Admin::RenounceOwnership, vec![]),
+        ] {
+            let tx = chain.admin(proxy.opcode(admin), &args)?;
+            assert_eq!(revert_code(&tx)?, 9, "{:?}", proxy);
+        }
+
+        let tx = chain.call(
+            Witness::new(),
+            &proxy_call(vec![proxy.opcode(Admin::GetAdmin)]),
+            &[],
+        )?;
+        assert_eq!(
+            u128_values(&return_data(&tx)?),
+            vec![2, 2, 0, 0, 1],
+            "{:?}",
+            proxy
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use crate::client::diesel::{build_diesel_call, diesel_inputs};
+use crate::client::payload::encode_payload;
+use crate::client::psbt::{finalize_reveal, funding_psbt, FundingUtxo};
+use crate::client::transaction::{sign_reveal, WitnessProxyCall};
+use crate::client::{SIGNET_DIESEL_PROXY, SIGNET_WITNESS_PROXY};
+use alkanes_proxy_support::witness::{extract_witness_payload, read_witness_cellpack};
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::bip32::{DerivationPath, Fingerprint, KeySource};
+use bitcoin::hashes::Hash;
+use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::{Keypair, Message, Secp256k1};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot;
+use bitcoin::TapSighashType;
+use bitcoin::{Amount, FeeRate, OutPoint, ScriptBuf, TxOut, Txid, Witness};
+use std::str::FromStr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_client_reveal_carries_payload() -> Result<()> {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32])?;
+    // Enough inputs that the payload spans several 520 byte pushes
+    let target = Cellpack {
+        target: AlkaneId { block: 2, tx: 1 },
+        inputs: (0..200).map(|i| u128::MAX - i).collect(),
+    };
+    let call = WitnessProxyCall {
+        proxy: SIGNET_WITNESS_PROXY,
+        target: target.clone(),
+        key: keypair.x_only_public_key().0,
+        funding: vec![OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 0,
+        }],
+        commit_value: Amount::from_sat(10_000),
+        change: None,
+        recipient: ScriptBuf::new_p2tr(&secp, keypair.x_only_public_key().0, None),
+        postage: Amount::from_sat(546),
+    };
+    let mut built = call.build(&secp)?;
+    sign_reveal(
+        &secp,
+        &mut built.reveal,
+        &built.commit.output[0],
+        &built.reveal_script,
+        &keypair,
+    )?;
+
+    assert_eq!(
+        extract_witness_payload(&built.reveal),
+        Some(encode_payload(&target))
+    );
+    let decoded = read_witness_cellpack(&built.reveal)?;
+    assert_eq!(decoded.target, target.target);
+    assert_eq!(decoded.inputs, target.inputs);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_client_fee_estimate_matches_built_vsize() -> Result<()> {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32])?;
+    let key = keypair.x_only_public_key().0;
+    let fee_rate = FeeRate::from_sat_per_vb_unchecked(7);
+    // Payloads needing each push size, just under and just over one full
+    // 520 byte push, and spanning several pushes
+    for input_count in [0u128, 10, 26, 27, 200] {
+        let target = Cellpack {
+            target: AlkaneId { block: 2, tx: 1 },
+            inputs: (0..input_count).map(|i| u128::MAX - i).collect(),
+        };
+        let call = WitnessProxyCall {
+            proxy: SIGNET_WITNESS_PROXY,
+            target,
+            key,
+            funding: vec![
+                OutPoint {
+                    txid: Txid::all_zeros(),
+                    vout: 0,
+                },
+                OutPoint {
+                    txid: Txid::all_zeros(),
+                    vout: 1,
+                },
+            ],
+            commit_value: Amount::from_sat(10_000),
+            change: Some(TxOut {
+                value: Amount::from_sat(50_000),
+                script_pubkey: ScriptBuf::new_p2tr(&secp, key, None),
+            }),
+            recipient: ScriptBuf::new_p2tr(&secp, key, None),
+            postage: Amount::from_sat(546),
+        };
+        let estimate = call.estimate_fees(fee_rate)?;
+        let mut built = call.build(&secp)?;
+        // Key path signatures of the funding inputs are 64 bytes
+        for input in built.commit.input.iter_mut() {
+            input.witness = Witness::from_slice(&[[0u8; 64]]);
+        }
+        sign_reveal(
+            &secp,
+            &mut built.reveal,
+            &built.commit.output[0],
+            &built.reveal_script,
+            &keypair,
+        )?;
+
+        assert_eq!(estimate.commit_vsize, built.commit.vsize() as u64);
+        assert_eq!(estimate.reveal_vsize, built.reveal.vsize() as u64);
+        assert_eq!(
+            estimate.reveal_fee,
+            fee_rate.fee_vb(built.reveal.vsize() as u64).unwrap()
+        );
+    }
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_client_psbts() -> Result<()> {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32])?;
+    let key = keypair.x_only_public_key().0;
+    let origin: KeySource = (
+        Fingerprint::from([0xde, 0xad, 0xbe, 0xef]),
+        DerivationPath::from_str("m/86'/1'/0'/0/0")?,
+    );
+    let funding = vec![FundingUtxo {
+        utxo: TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, key, None),
+        },
+        tap_internal_key: Some(key),
+        key_origin: Some(origin.clone()),
+    }];
+    let outpoint = OutPoint {
+        txid: Txid::all_zeros(),
+        vout: 0,
+    };
+    let target = Cellpack {
+        target: AlkaneId { block: 2, tx: 1 },
+        inputs: vec![22, 1000],
+    };
+    let call = WitnessProxyCall {
+        proxy: SIGNET_WITNESS_PROXY,
+        target: target.clone(),
+        key,
+        funding: vec![outpoint],
+        commit_value: Amount::from_sat(10_000),
+        change: None,
+        recipient: ScriptBuf::new_p2tr(&secp, key, None),
+        postage: Amount::from_sat(546),
+    };
+    let (commit, mut reveal) = call
+        .build(&secp)?
+        .to_psbts(&funding, Some(origin.clone()))?;
+    assert_eq!(commit.inputs[0].witness_utxo, Some(funding[0].utxo.clone()));
+    assert_eq!(
+        commit.inputs[0].tap_key_origins.get(&key),
+        Some(&(Vec::new(), origin.clone()))
+    );
+    assert!(commit.outputs[0].tap_tree.is_some());
+
+    // What an external signer does with the tapscript leaf of the reveal
+    let (control_block, (script, leaf_version)) =
+        reveal.inputs[0].tap_scripts.iter().next().unwrap();
+    let leaf_hash = taproot::TapLeafHash::from_script(script, *leaf_version);
+    assert_eq!(
+        reveal.inputs[0].tap_key_origins.get(&key),
+        Some(&(vec![leaf_hash], origin))
+    );
+    let prevout = reveal.inputs[0].witness_utxo.clone().unwrap();
+    assert_eq!(
+        prevout.script_pubkey,
+        ScriptBuf::new_p2tr_tweaked(
+            control_block
+                .internal_key
+                .tap_tweak(&secp, reveal.inputs[0].tap_merkle_root)
+                .0
+        )
+    );
+    let sighash = SighashCache::new(&reveal.unsigned_tx).taproot_script_spend_signature_hash(
+        0,
+        &Prevouts::All(&[prevout]),
+        leaf_hash,
+        TapSighashType::Default,
+    )?;
+    let signature = taproot::Signature {
+        signature: secp
+            .sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), &keypair),
+        sighash_type: TapSighashType::Default,
+    };
+    reveal.inputs[0]
+        .tap_script_sigs
+        .insert((key, leaf_hash), signature);
+    let reveal = finalize_reveal(reveal)?;
+    assert_eq!(
+        extract_witness_payload(&reveal),
+        Some(encode_payload(&target))
+    );
+
+    let diesel = funding_psbt(
+        build_diesel_call(
+            &[outpoint],
+            ScriptBuf::new_p2tr(&secp, key, None),
+            Amount::from_sat(546),
+            None,
+            SIGNET_DIESEL_PROXY,
+            &target,
+        )?,
+        &funding,
+    )?;
+    assert_eq!(diesel.inputs[0].tap_internal_key, Some(key));
+    assert!(diesel.unsigned_tx.output[1].script_pubkey.is_op_return());
+    assert_eq!(diesel_inputs(&target), vec![2, 1, 22, 1000]);
+
+    Ok(())
+}
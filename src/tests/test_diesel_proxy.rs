@@ -1,87 +1,229 @@
-use crate::tests::std::diesel_proxy_build;
-use alkanes::indexer::index_block;
-use alkanes::message::AlkaneMessageContext;
-use alkanes::precompiled::alkanes_std_auth_token_build;
-use alkanes::tests::helpers::{self as alkane_helpers, clear};
-use alkanes::view;
-use alkanes_support::cellpack::Cellpack;
-use alkanes_support::constants::AUTH_TOKEN_FACTORY_ID;
-use alkanes_support::id::AlkaneId;
-use alkanes_support::response::ExtendedCallResponse;
-use alkanes_support::trace::{Trace, TraceEvent};
+use crate::client::payload::encode_payload;
+use crate::harness::{auth_token_factory, diesel_proxy, test_alkane, TEST_ALKANE};
+use crate::tests::helpers::{
+    envelope_witness, incoming_to, mint, proxy_call, return_data, revert_code, u128_values, Chain,
+    DIESEL,
+};
 use anyhow::Result;
-use bitcoin::hashes::Hash;
-
-use bitcoin::address::NetworkChecked;
-use bitcoin::blockdata::transaction::OutPoint;
-use bitcoin::transaction::Version;
-use bitcoin::{Address, Amount, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
-use metashrew_core::{get_cache, index_pointer::IndexPointer, println, stdio::stdout};
-use metashrew_support::index_pointer::KeyValuePointer;
-use metashrew_support::utils::consensus_encode;
-use ordinals::{Etching, Rune, Runestone};
-use protorune::balance_sheet::load_sheet;
-use protorune::message::MessageContext;
-use protorune::protostone::Protostones;
-use protorune::tables::RuneTable;
-use protorune::test_helpers::{create_block_with_coinbase_tx, get_address, ADDRESS1};
-use protorune_support::balance_sheet::{BalanceSheet, BalanceSheetOperations, ProtoruneRuneId};
-use protorune_support::protostone::{Protostone, ProtostoneEdict};
-use std::fmt::Write;
-use std::str::FromStr;
+use bitcoin::Witness;
 use wasm_bindgen_test::wasm_bindgen_test;
 
+/// Mints DIESEL with opcode `77` on every call.
+const MINT_CONFIG: [u128; 4] = [0, 2, 0, 77];
+
+/// An alkane that does not exist, so minting from it always fails.
+const MISSING_ALKANE: [u128; 2] = [2, 99];
+
+/// What a single DIESEL mint yields at the test height.
+const DIESEL_MINT: u128 = 312500000;
+
+fn setup(config: &[u128]) -> Result<Chain> {
+    Chain::new(&[auth_token_factory(), test_alkane(), diesel_proxy(1, config)])
+}
+
 #[wasm_bindgen_test]
 fn test_diesel_proxy() -> Result<()> {
-    clear();
-    let block_height = 880_000;
-
-    // Create a cellpack to call the process_numbers method (opcode 11)
-    let init_test_cellpack = Cellpack {
-        target: AlkaneId { block: 1, tx: 0 },
-        inputs: vec![50],
-    };
-    let init_cellpack = Cellpack {
-        target: AlkaneId { block: 3, tx: 1 },
-        inputs: vec![69690420],
-    };
-
-    let test_build = include_bytes!("./precompiled/alkanes_std_test.wasm").to_vec();
-
-    // Initialize the contract and execute the cellpacks
-    let mut test_block = alkane_helpers::init_with_multiple_cellpacks_with_tx(
-        [test_build, diesel_proxy_build::get_bytes()].into(),
-        [init_test_cellpack, init_cellpack].into(),
-    );
-
-    // Create a cellpack to call the process_numbers method (opcode 11)
-    let arb_mint_cellpack = Cellpack {
-        target: AlkaneId { block: 4, tx: 1 },
-        inputs: vec![2, 1, 22, 100000],
-    };
-
-    test_block
-        .txdata
-        .push(alkane_helpers::create_multiple_cellpack_with_witness(
-            Witness::new(),
-            vec![arb_mint_cellpack],
-            false,
-        ));
-
-    index_block(&test_block, block_height)?;
-
-    let sheet = alkane_helpers::get_last_outpoint_sheet(&test_block)?;
-
-    println!("Last sheet: {:?}", sheet);
-
-    assert_eq!(
-        sheet.get_cached(&ProtoruneRuneId { block: 2, tx: 0 }),
-        312500000
-    );
-    assert_eq!(
-        sheet.get_cached(&ProtoruneRuneId { block: 2, tx: 1 }),
-        100000
-    );
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    // The fallback mints DIESEL and then mints 100000 of the test alkane
+    chain.call(
+        Witness::new(),
+        &proxy_call(vec![TEST_ALKANE.block, TEST_ALKANE.tx, 22, 100000]),
+        &[],
+    )?;
+
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 100000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_only() -> Result<()> {
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    chain.call(Witness::new(), &proxy_call(vec![69690424]), &[])?;
+
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_call_only() -> Result<()> {
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    chain.call(
+        Witness::new(),
+        &proxy_call(vec![69690425, TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]),
+        &[],
+    )?;
+
+    assert_eq!(chain.balance(DIESEL)?, 0);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_and_call() -> Result<()> {
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    chain.call(
+        Witness::new(),
+        &proxy_call(vec![69690426, TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]),
+        &[],
+    )?;
+
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_rejects_short_inputs() -> Result<()> {
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    // A target block without a target tx, through the fallback and through
+    // MintAndCall
+    for inputs in [vec![2], vec![69690426, 2]] {
+        let tx = chain.call(Witness::new(), &proxy_call(inputs), &[])?;
+        assert_eq!(revert_code(&tx)?, 3);
+    }
+    assert_eq!(chain.balance(DIESEL)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_get_mint_config() -> Result<()> {
+    let config = [1, 2, 0, 77, TEST_ALKANE.block, TEST_ALKANE.tx, 22];
+    let mut chain = setup(&config)?;
+
+    let tx = chain.call(Witness::new(), &proxy_call(vec![69690421]), &[])?;
+
+    assert_eq!(u128_values(&return_data(&tx)?), config.to_vec());
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_default_mint_config() -> Result<()> {
+    // Initialized with `[69690420]` only, as deploy scripts did before the
+    // mint became configurable
+    let mut chain = setup(&[])?;
+
+    let tx = chain.call(Witness::new(), &proxy_call(vec![69690421]), &[])?;
+    assert_eq!(u128_values(&return_data(&tx)?), MINT_CONFIG.to_vec());
+
+    chain.call(
+        Witness::new(),
+        &proxy_call(vec![TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]),
+        &[],
+    )?;
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_best_effort() -> Result<()> {
+    // Strict policy, but the second target always fails to mint
+    let mut chain = setup(&[0, 2, 0, 77, MISSING_ALKANE[0], MISSING_ALKANE[1], 77])?;
+
+    let tx = chain.call(
+        Witness::new(),
+        &proxy_call(vec![69690422, TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]),
+        &[],
+    )?;
+
+    assert_eq!(return_data(&tx)?[..2], [1, 0]);
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_policy() -> Result<()> {
+    let call = proxy_call(vec![TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]);
+    let targets = [2, 0, 77, MISSING_ALKANE[0], MISSING_ALKANE[1], 77];
+
+    // Skipping the failed mint, DIESEL is still minted and the call runs
+    let mut config = vec![1];
+    config.extend(targets);
+    let mut chain = setup(&config)?;
+    chain.call(Witness::new(), &call, &[])?;
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    // The strict policy reverts everything
+    config[0] = 0;
+    let mut chain = setup(&config)?;
+    let tx = chain.call(Witness::new(), &call, &[])?;
+    assert_eq!(revert_code(&tx)?, 13);
+    assert_eq!(chain.balance(DIESEL)?, 0);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_returned_nothing() -> Result<()> {
+    // The mint target is `GetMintConfig` of a second diesel proxy at 4:2,
+    // which succeeds without returning any alkanes
+    let mut chain = Chain::new(&[
+        auth_token_factory(),
+        test_alkane(),
+        diesel_proxy(1, &[0, 4, 2, 69690421]),
+        diesel_proxy(2, &MINT_CONFIG),
+    ])?;
+
+    let tx = chain.call(
+        Witness::new(),
+        &proxy_call(vec![TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000]),
+        &[],
+    )?;
+
+    assert_eq!(revert_code(&tx)?, 5);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_into_call() -> Result<()> {
+    let target = [TEST_ALKANE.block, TEST_ALKANE.tx, 22, 1000];
+
+    // The fallback pays the mint out after the call; MintIntoCall sends it
+    // into the call
+    for (prefix, fed) in [
+        (vec![], vec![]),
+        (vec![69690423], vec![(DIESEL, DIESEL_MINT)]),
+    ] {
+        let mut chain = setup(&MINT_CONFIG)?;
+        let mut inputs = prefix;
+        inputs.extend(target);
+        let tx = chain.call(Witness::new(), &proxy_call(inputs), &[])?;
+
+        assert_eq!(incoming_to(&tx, TEST_ALKANE)?, vec![fed]);
+        assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+    }
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_diesel_proxy_mint_and_call_from_witness() -> Result<()> {
+    let mut chain = setup(&MINT_CONFIG)?;
+
+    let payload = encode_payload(&mint(1000));
+    chain.call(envelope_witness(&payload), &proxy_call(vec![69690427]), &[])?;
+
+    assert_eq!(chain.balance(DIESEL)?, DIESEL_MINT);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
 
     Ok(())
 }
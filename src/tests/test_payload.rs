@@ -0,0 +1,146 @@
+use crate::client::payload::{cellpack_values, encode_payload, encode_values};
+use crate::tests::helpers::{call_transaction, envelope_witness, envelopes_witness, PROXY};
+use alkanes_proxy_support::payload::{
+    decode_single, decode_values, PayloadHeader, FLAG_ASSERTIONS, FLAG_DEFLATE, FLAG_GZIP,
+    MAX_DECOMPRESSED_LEN, PAYLOAD_MAGIC, PAYLOAD_VERSION,
+};
+use alkanes_proxy_support::witness::{extract_witness_payload, read_witness_cellpack};
+use alkanes_proxy_support::ProxyError;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Transaction, Txid, Witness};
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
+use protorune_support::utils::encode_varint_list;
+use std::io::Write;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn cellpack() -> Cellpack {
+    Cellpack {
+        target: AlkaneId { block: 2, tx: 1 },
+        inputs: vec![22, 1000],
+    }
+}
+
+fn header(version: u8, flags: u8) -> Vec<u8> {
+    PayloadHeader { version, flags }.encode()
+}
+
+fn error_code(result: Result<impl std::fmt::Debug>) -> Option<u32> {
+    let error = result.expect_err("payload should be rejected");
+    error.downcast_ref::<ProxyError>().map(ProxyError::code)
+}
+
+fn deflate(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn witness_tx(witness: Witness) -> Result<Transaction> {
+    call_transaction(
+        OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 0,
+        },
+        witness,
+        &Cellpack {
+            target: PROXY,
+            inputs: vec![0],
+        },
+        &[],
+    )
+}
+
+fn decode(payload: &[u8]) -> Result<Cellpack> {
+    let (header, body) = PayloadHeader::parse(payload)?;
+    decode_single(decode_values(header.decode_body(body)?)?)
+}
+
+#[wasm_bindgen_test]
+fn test_payload_round_trip() -> Result<()> {
+    assert_eq!(decode(&encode_payload(&cellpack()))?, cellpack());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_payload_rejects_bad_magic() {
+    let mut payload = encode_payload(&cellpack());
+    payload[..PAYLOAD_MAGIC.len()].copy_from_slice(b"XPXY");
+    assert!(!PayloadHeader::is_tagged(&payload));
+    assert_eq!(error_code(PayloadHeader::parse(&payload)), Some(6));
+}
+
+#[wasm_bindgen_test]
+fn test_payload_rejects_unknown_version() {
+    let payload = header(PAYLOAD_VERSION + 1, 0);
+    assert_eq!(error_code(PayloadHeader::parse(&payload)), Some(6));
+}
+
+#[wasm_bindgen_test]
+fn test_payload_rejects_unknown_flags() {
+    let payload = header(PAYLOAD_VERSION, 0x10);
+    assert_eq!(error_code(PayloadHeader::parse(&payload)), Some(6));
+}
+
+#[wasm_bindgen_test]
+fn test_payload_rejects_deflate_and_gzip() {
+    let payload = header(PAYLOAD_VERSION, FLAG_DEFLATE | FLAG_GZIP);
+    assert_eq!(error_code(PayloadHeader::parse(&payload)), Some(6));
+}
+
+#[wasm_bindgen_test]
+fn test_payload_deflate_round_trip() -> Result<()> {
+    let mut payload = header(PAYLOAD_VERSION, FLAG_DEFLATE);
+    payload.extend(deflate(&encode_varint_list(&cellpack_values(&cellpack()))));
+    assert_eq!(decode(&payload)?, cellpack());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_payload_gzip_round_trip() -> Result<()> {
+    let mut payload = header(PAYLOAD_VERSION, FLAG_GZIP);
+    payload.extend(gzip(&encode_varint_list(&cellpack_values(&cellpack()))));
+    assert_eq!(decode(&payload)?, cellpack());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_payload_rejects_oversized_body() {
+    let mut payload = header(PAYLOAD_VERSION, FLAG_DEFLATE);
+    payload.extend(deflate(&vec![0; MAX_DECOMPRESSED_LEN + 1]));
+    assert_eq!(error_code(decode(&payload)), Some(6));
+}
+
+#[wasm_bindgen_test]
+fn test_payload_skips_untagged_envelope() -> Result<()> {
+    // An inscription style envelope comes first; the proxy payload is the
+    // second envelope of the same input
+    let untagged = b"not a proxy payload".to_vec();
+    let tagged = encode_values(&cellpack_values(&cellpack()), 0);
+    let tx = witness_tx(envelopes_witness(&[&untagged, &tagged]))?;
+    assert_eq!(extract_witness_payload(&tx), Some(tagged));
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_read_witness_cellpack_rejects_assertions() -> Result<()> {
+    // A MinReturned assertion in front of the cellpack, which contracts
+    // reading a single cellpack must not mistake for the target
+    let mut values = vec![1, 0, 2, 1, 5000];
+    values.extend(cellpack_values(&cellpack()));
+    let tx = witness_tx(envelope_witness(&encode_values(&values, FLAG_ASSERTIONS)))?;
+    assert_eq!(error_code(read_witness_cellpack(&tx)), Some(6));
+
+    let tx = witness_tx(envelope_witness(&encode_payload(&cellpack())))?;
+    assert_eq!(read_witness_cellpack(&tx)?, cellpack());
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::client::payload::{batch_values, cellpack_values, encode_payload, encode_signed_values};
+use crate::harness::{auth_token_factory, test_alkane, witness_proxy, TEST_ALKANE};
+use crate::tests::helpers::{
+    envelope_witness, mint, proxy_call, return_data, revert_code, u128_values, Chain, PROXY,
+};
+use anyhow::Result;
+use bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey};
+use bitcoin::Witness;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const FALLBACK: u8 = 0;
+const BATCH: u8 = 1;
+
+fn keypair(byte: u8) -> Keypair {
+    Keypair::from_secret_key(
+        &Secp256k1::new(),
+        &SecretKey::from_slice(&[byte; 32]).expect("valid secret key"),
+    )
+}
+
+/// The two little endian halves the proxy is initialized with.
+fn key_halves(keypair: &Keypair) -> [u128; 2] {
+    let key = keypair.x_only_public_key().0.serialize();
+    [
+        u128::from_le_bytes(key[..16].try_into().unwrap()),
+        u128::from_le_bytes(key[16..].try_into().unwrap()),
+    ]
+}
+
+fn setup(signer: &Keypair) -> Result<Chain> {
+    Chain::new(&[
+        auth_token_factory(),
+        test_alkane(),
+        witness_proxy(1, key_halves(signer)),
+    ])
+}
+
+fn signed_mint(signer: &Keypair, mode: u8, nonce: u128, amount: u128) -> Vec<u8> {
+    let values = if mode == FALLBACK {
+        cellpack_values(&mint(amount))
+    } else {
+        batch_values(&[mint(amount)])
+    };
+    encode_signed_values(&Secp256k1::new(), signer, &PROXY, mode, nonce, &values, 0)
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_executes() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    chain.call(
+        envelope_witness(&signed_mint(&signer, FALLBACK, 0, 1000)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_rejects_wrong_key() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    let tx = chain.call(
+        envelope_witness(&signed_mint(&keypair(2), FALLBACK, 0, 1000)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+
+    assert_eq!(revert_code(&tx)?, 7);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_rejects_tampered_bytes() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    // Raise the minted amount after signing; 1000 is the last varint
+    let mut payload = signed_mint(&signer, FALLBACK, 0, 1000);
+    let last = payload.len() - 1;
+    payload[last] += 1;
+    let tx = chain.call(envelope_witness(&payload), &proxy_call(vec![0]), &[])?;
+
+    assert_eq!(revert_code(&tx)?, 7);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_rejects_other_mode() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    // Signed to run as a batch but sent to the pipe opcode
+    let tx = chain.call(
+        envelope_witness(&signed_mint(&signer, BATCH, 0, 1000)),
+        &proxy_call(vec![69692]),
+        &[],
+    )?;
+
+    assert_eq!(revert_code(&tx)?, 7);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_rejects_unsigned() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    let tx = chain.call(
+        envelope_witness(&encode_payload(&mint(1000))),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+
+    assert_eq!(revert_code(&tx)?, 7);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_signed_payload_rejects_replay() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+
+    let payload = signed_mint(&signer, FALLBACK, 0, 1000);
+    chain.call(envelope_witness(&payload), &proxy_call(vec![0]), &[])?;
+    let replay = chain.call(envelope_witness(&payload), &proxy_call(vec![0]), &[])?;
+
+    assert_eq!(revert_code(&replay)?, 7);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_get_nonce() -> Result<()> {
+    let signer = keypair(1);
+    let mut chain = setup(&signer)?;
+    let [key_0, key_1] = key_halves(&signer);
+
+    let tx = chain.call(Witness::new(), &proxy_call(vec![69694, key_0, key_1]), &[])?;
+    assert_eq!(u128_values(&return_data(&tx)?), vec![0]);
+
+    chain.call(
+        envelope_witness(&signed_mint(&signer, FALLBACK, 0, 1000)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+    let tx = chain.call(Witness::new(), &proxy_call(vec![69694, key_0, key_1]), &[])?;
+    assert_eq!(u128_values(&return_data(&tx)?), vec![1]);
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::client::diesel::build_diesel_call;
+use crate::client::SIGNET_DIESEL_PROXY;
+use crate::simulator::{default_fixtures, Simulator};
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::hashes::Hash;
+use bitcoin::{Amount, OutPoint, ScriptBuf, Txid};
+use protorune_support::balance_sheet::ProtoruneRuneId;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_simulator_diesel_call() -> Result<()> {
+    let mut simulator = Simulator::new(880_000, &default_fixtures())?;
+    let tx = build_diesel_call(
+        &[OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 0,
+        }],
+        ScriptBuf::new(),
+        Amount::from_sat(546),
+        None,
+        SIGNET_DIESEL_PROXY,
+        &Cellpack {
+            target: AlkaneId { block: 2, tx: 1 },
+            inputs: vec![22, 1000],
+        },
+    )?;
+
+    let simulation = simulator.simulate(&tx)?;
+
+    assert_eq!(simulation.traces.len(), 1);
+    let (_, balances) = &simulation.sheets[0];
+    assert!(balances.contains(&(ProtoruneRuneId { block: 2, tx: 0 }, 312500000)));
+    assert!(balances.contains(&(ProtoruneRuneId { block: 2, tx: 1 }, 1000)));
+
+    Ok(())
+}
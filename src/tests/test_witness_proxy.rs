@@ -0,0 +1,292 @@
+use crate::client::payload::{
+    batch_values, cellpack_values, encode_batch_payload, encode_payload, encode_values,
+};
+use crate::harness::{auth_token_factory, diesel_proxy, test_alkane, witness_proxy, TEST_ALKANE};
+use crate::tests::helpers::{
+    envelope_witness, envelopes_witness, incoming_to, mint, proxy_call, return_data, revert_code,
+    u128_values, Chain,
+};
+use alkanes_proxy_support::payload::FLAG_ASSERTIONS;
+use alkanes_support::cellpack::Cellpack;
+use alkanes_support::id::AlkaneId;
+use anyhow::Result;
+use bitcoin::Witness;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn setup() -> Result<Chain> {
+    Chain::new(&[
+        auth_token_factory(),
+        test_alkane(),
+        witness_proxy(1, [0, 0]),
+    ])
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_batch() -> Result<()> {
+    let mut chain = setup()?;
+
+    // Two mints of the test alkane carried in a single witness payload
+    let payload = encode_batch_payload(&[mint(1000), mint(2000)]);
+    let tx = chain.call(envelope_witness(&payload), &proxy_call(vec![69691]), &[])?;
+    let data = return_data(&tx)?;
+
+    assert_eq!(chain.balance(TEST_ALKANE)?, 3000);
+
+    // The data of both calls, each prefixed with its length, matches what
+    // the same mints return when made directly
+    let mut expected = Vec::new();
+    for amount in [1000, 2000] {
+        let direct = return_data(&chain.call(Witness::new(), &mint(amount), &[])?)?;
+        expected.extend((direct.len() as u128).to_le_bytes());
+        expected.extend(direct);
+    }
+    assert_eq!(data, expected);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_try() -> Result<()> {
+    let mut chain = setup()?;
+
+    // 500 of the test alkane to send into the batch, which its first call
+    // receives
+    chain.call(Witness::new(), &mint(500), &[])?;
+
+    // The first call targets an alkane that does not exist; the second one
+    // still mints
+    let payload = encode_batch_payload(&[
+        Cellpack {
+            target: AlkaneId { block: 2, tx: 99 },
+            inputs: vec![22, 1000],
+        },
+        mint(2000),
+    ]);
+    let tx = chain.call(
+        envelope_witness(&payload),
+        &proxy_call(vec![69707]),
+        &[(TEST_ALKANE, 500)],
+    )?;
+    let data = return_data(&tx)?;
+
+    // The 500 sent into the failed call are refunded to the tip
+    assert_eq!(chain.balance(TEST_ALKANE)?, 2500);
+
+    // `[success, error code, data length]` per call: the first failed with
+    // code 4, then the data of the second one
+    let (status, returned) = data.split_at(6 * 16);
+    let status = u128_values(status);
+    assert_eq!(status[..5], [0, 4, 0, 1, 0]);
+    assert_eq!(status[5], returned.len() as u128);
+    let direct = return_data(&chain.call(Witness::new(), &mint(2000), &[])?)?;
+    assert_eq!(returned, direct);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_assertion_reverts() -> Result<()> {
+    let mut chain = setup()?;
+
+    // Mints 1000 of the test alkane but asserts at least 5000 come back
+    let mut values = vec![1, 0, TEST_ALKANE.block, TEST_ALKANE.tx, 5000];
+    values.extend(cellpack_values(&mint(1000)));
+    let tx = chain.call(
+        envelope_witness(&encode_values(&values, FLAG_ASSERTIONS)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+
+    assert_eq!(revert_code(&tx)?, 10);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_min_returned() -> Result<()> {
+    let mut chain = setup()?;
+
+    // Mints 1000 of the test alkane and asserts at least 1000 come back
+    let mut values = vec![1, 0, TEST_ALKANE.block, TEST_ALKANE.tx, 1000];
+    values.extend(cellpack_values(&mint(1000)));
+    let tx = chain.call(
+        envelope_witness(&encode_values(&values, FLAG_ASSERTIONS)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+
+    return_data(&tx)?;
+    assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_max_consumed() -> Result<()> {
+    let mut chain = setup()?;
+    chain.call(Witness::new(), &mint(500), &[])?;
+
+    // Opcode 3 of the test alkane accepts a single incoming transfer and
+    // keeps it, so all 500 sent into the call are consumed
+    let keep = Cellpack {
+        target: TEST_ALKANE,
+        inputs: vec![3],
+    };
+    let payload = |max: u128| {
+        let mut values = vec![1, 1, TEST_ALKANE.block, TEST_ALKANE.tx, max];
+        values.extend(cellpack_values(&keep));
+        envelope_witness(&encode_values(&values, FLAG_ASSERTIONS))
+    };
+
+    let tx = chain.call(payload(499), &proxy_call(vec![0]), &[(TEST_ALKANE, 500)])?;
+    assert_eq!(revert_code(&tx)?, 10);
+    assert_eq!(chain.balance(TEST_ALKANE)?, 500);
+
+    let tx = chain.call(payload(500), &proxy_call(vec![0]), &[(TEST_ALKANE, 500)])?;
+    return_data(&tx)?;
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_payload_errors() -> Result<()> {
+    let mut chain = setup()?;
+
+    // No envelope at all
+    let tx = chain.call(Witness::new(), &proxy_call(vec![0]), &[])?;
+    assert_eq!(revert_code(&tx)?, 1);
+
+    // A varint whose continuation bit runs past the end of the payload
+    let mut payload = encode_values(&[], 0);
+    payload.push(0x80);
+    let tx = chain.call(envelope_witness(&payload), &proxy_call(vec![0]), &[])?;
+    assert_eq!(revert_code(&tx)?, 2);
+
+    // A target block without a target tx
+    let tx = chain.call(
+        envelope_witness(&encode_values(&[TEST_ALKANE.block], 0)),
+        &proxy_call(vec![0]),
+        &[],
+    )?;
+    assert_eq!(revert_code(&tx)?, 3);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_decode_payload() -> Result<()> {
+    let mut chain = setup()?;
+
+    // A batch of two mints behind a MinReturned assertion, decoded as a batch
+    let mut values = vec![1, 0, TEST_ALKANE.block, TEST_ALKANE.tx, 5000];
+    values.extend(batch_values(&[mint(1000), mint(2000)]));
+    let tx = chain.call(
+        envelope_witness(&encode_values(&values, FLAG_ASSERTIONS)),
+        &proxy_call(vec![69695, 1]),
+        &[],
+    )?;
+    assert_eq!(
+        u128_values(&return_data(&tx)?),
+        vec![0, 1, 0, 2, 1, 5000, 2, 4, 2, 1, 22, 1000, 4, 2, 1, 22, 2000]
+    );
+
+    // A single cellpack read from an explicit envelope
+    let tx = chain.call(
+        envelope_witness(&encode_payload(&mint(1000))),
+        &proxy_call(vec![69708, 0, 0, 0]),
+        &[],
+    )?;
+    assert_eq!(
+        u128_values(&return_data(&tx)?),
+        vec![0, 0, 1, 4, 2, 1, 22, 1000]
+    );
+
+    // Nothing was called
+    assert_eq!(chain.balance(TEST_ALKANE)?, 0);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_rejects_unknown_mode() -> Result<()> {
+    let mut chain = setup()?;
+
+    let tx = chain.call(
+        envelope_witness(&encode_payload(&mint(1000))),
+        &proxy_call(vec![69693, 0, 0, 9]),
+        &[],
+    )?;
+    assert_eq!(revert_code(&tx)?, 11);
+
+    let tx = chain.call(
+        envelope_witness(&encode_payload(&mint(1000))),
+        &proxy_call(vec![69693, u32::MAX as u128 + 1, 0, 0]),
+        &[],
+    )?;
+    assert_eq!(revert_code(&tx)?, 11);
+
+    let tx = chain.admin(69696, &[3])?;
+    assert_eq!(revert_code(&tx)?, 11);
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_pipe() -> Result<()> {
+    // The diesel proxy at 4:2 forwards whatever it receives from
+    // `GetMintConfig`, which makes it a pass-through second call
+    let forwarder = AlkaneId { block: 4, tx: 2 };
+    let forward = Cellpack {
+        target: forwarder,
+        inputs: vec![69690421],
+    };
+    let payload = encode_batch_payload(&[mint(1000), forward]);
+
+    for (opcode, piped) in [(69692, vec![(TEST_ALKANE, 1000)]), (69691, vec![])] {
+        let mut chain = Chain::new(&[
+            auth_token_factory(),
+            test_alkane(),
+            witness_proxy(1, [0, 0]),
+            diesel_proxy(2, &[0, 2, 0, 77]),
+        ])?;
+        let tx = chain.call(envelope_witness(&payload), &proxy_call(vec![opcode]), &[])?;
+
+        // Only the pipe hands the minted alkanes to the second call, which
+        // returns them
+        assert_eq!(incoming_to(&tx, forwarder)?, vec![piped]);
+        assert_eq!(chain.balance(TEST_ALKANE)?, 1000);
+    }
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_witness_proxy_execute_from_input() -> Result<()> {
+    let mut chain = setup()?;
+
+    // Both envelopes are tagged; scanning would run the first one
+    let first = encode_payload(&mint(1000));
+    let second = encode_payload(&mint(2000));
+    chain.call(
+        envelopes_witness(&[&first, &second]),
+        &proxy_call(vec![69693, 0, 1, 0]),
+        &[],
+    )?;
+    assert_eq!(chain.balance(TEST_ALKANE)?, 2000);
+
+    // The envelope and the mode default to 0
+    chain.call(
+        envelopes_witness(&[&first, &second]),
+        &proxy_call(vec![69693, 0]),
+        &[],
+    )?;
+    assert_eq!(chain.balance(TEST_ALKANE)?, 3000);
+
+    // The input index is required
+    let tx = chain.call(envelope_witness(&first), &proxy_call(vec![69693]), &[])?;
+    assert_eq!(revert_code(&tx)?, 11);
+
+    Ok(())
+}